@@ -0,0 +1,59 @@
+use crate::lexer::{Span, Token};
+
+#[derive(Debug, Clone, PartialEq)]
+/// A whole line of input: `AndOr` lists separated by `;`. Each list always runs.
+pub struct CommandList {
+    pub items: Vec<AndOr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Pipelines joined by `&&` and `||`, which decide whether each pipeline runs based on the
+/// status of the last pipeline that ran.
+pub struct AndOr {
+    pub first: Pipeline,
+    pub rest: Vec<(AndOrOperator, Pipeline)>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// An operator joining two pipelines in an `AndOr` list.
+pub enum AndOrOperator {
+    /// `&&`, which runs the next pipeline if the last one succeeded.
+    And,
+
+    /// `||`, which runs the next pipeline if the last one failed.
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Commands joined by `|`.
+pub struct Pipeline {
+    pub commands: Vec<SimpleCommand>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A command name followed by its arguments, such as `mkdir -p a/b`.
+pub struct SimpleCommand {
+    pub name: String,
+    pub name_span: Span,
+    pub words: Vec<Word>,
+
+    /// The span from the start of the name to the end of the last word.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A single argument: a path, a number or a flag. A word is made of the tokens between spaces.
+pub struct Word {
+    pub tokens: Vec<Token>,
+    pub span: Span,
+}
+
+impl AndOr {
+    /// Get every pipeline in the list with the operator before it.
+    /// The first pipeline has no operator.
+    pub fn pipelines(&self) -> impl Iterator<Item = (Option<AndOrOperator>, &Pipeline)> {
+        std::iter::once((None, &self.first))
+            .chain(self.rest.iter().map(|(operator, pipeline)| (Some(*operator), pipeline)))
+    }
+}
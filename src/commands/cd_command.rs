@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use std::rc::Rc;
 
 use crate::Context;
+use super::{CommandError, CommandOutput};
 use crate::tree::Node;
 use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
 
@@ -23,10 +24,10 @@ impl super::Command for CdCmd {
 
         // make sure the type is a path
         if let Argument::Path(node_path) = &arguments[0] {
-            // Assure that we are not changing directory to a file or to the tree root.
+            // Assure that we are not changing directory to the tree root.
             // The home folder, which is a child of the root, should be the root that is accessible
             // to a user.
-            if let NodePathSegment::File(..) | NodePathSegment::Root = node_path.last().unwrap() {
+            if let NodePathSegment::Root = node_path.last().unwrap() {
                 return Err(SyntaxError::InvalidType);
             }
         
@@ -39,14 +40,18 @@ impl super::Command for CdCmd {
     }
 
     /// Execute the CdCmd. This changes the current directory to the path supplied
-    fn execute(&self, ctx: Rc<Context>) {
-        let target = ctx.node_from_path(&self.path);
-        if let Ok(target) = target {
-            if let Node::Root { .. } = *target {
-                println!("No parent folder");
-            } else {
-                ctx.set_current_dir(target);
-            }
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let path = super::single(super::expand(&ctx, &self.path)?, &self.path)?;
+        let target = ctx.find_node(&path)?;
+        if target.children().is_none() {
+            return Err(CommandError::NotADirectory(target.to_string()));
+        }
+
+        if let Node::Root { .. } = *target {
+            return Err(CommandError::PermissionDenied("the root is not a folder that can be entered".to_string()));
         }
+
+        ctx.set_current_dir(target);
+        Ok(CommandOutput::new())
     }
 }
@@ -0,0 +1,149 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
+use crate::tree::Node;
+
+#[derive(Debug)]
+pub struct CpCmd {
+    sources: Vec<NodePath>,
+    destination: NodePath,
+
+    /// Whether folders can be copied along with everything inside them.
+    recursive: bool,
+}
+
+impl super::Command for CpCmd {
+    /// Build a CpCmd.
+    /// Takes in an array of arguments. The last path is where to copy to and the paths before
+    /// it are the files and folders to copy. `-r` or `-R` is needed to copy a folder.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        let mut recursive = false;
+        let mut paths = Vec::new();
+
+        for argument in arguments.iter() {
+            match argument {
+                Argument::Flag(flag) => match flag.as_str() {
+                    "-r" | "-R" => recursive = true,
+                    _ => return Err(SyntaxError::InvalidFlag),
+                },
+                Argument::Path(path) => paths.push(path.clone()),
+                _ => return Err(SyntaxError::InvalidType),
+            }
+        }
+
+        // validate path count
+        let Some((destination, sources)) = paths.split_last().filter(|(_, sources)| !sources.is_empty()) else {
+            return Err(SyntaxError::InvalidArguments);
+        };
+
+        // the tree root cannot be copied
+        if sources.iter().any(|source| matches!(source.last(), Some(NodePathSegment::Root) | None)) {
+            return Err(SyntaxError::InvalidPath);
+        }
+
+        Ok(Self {
+            sources: sources.to_vec(),
+            destination: destination.clone(),
+            recursive,
+        })
+    }
+
+    /// Execute the cp command. This copies each file, or folder and its contents, to a new
+    /// location. Several sources can only be copied into a folder. Existing files and folders
+    /// are never overwritten.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let sources = super::expand_all(&ctx, &self.sources)?;
+        let destination = super::single(super::expand_new(&ctx, &self.destination)?, &self.destination)?;
+        if sources.len() > 1 {
+            super::check_destination_folder(&ctx, &destination)?;
+        }
+
+        for source in sources.iter() {
+            self.copy(&ctx, ctx.find_node(source)?, &destination)?;
+        }
+
+        Ok(CommandOutput::new())
+    }
+}
+
+impl CpCmd {
+    /// Copy `source` to `destination`.
+    fn copy(&self, ctx: &Context, source: Rc<Node>, destination: &NodePath) -> Result<(), CommandError> {
+        if let Node::Root { .. } = *source {
+            return Err(CommandError::PermissionDenied("cannot copy the root".to_string()));
+        }
+
+        if source.children().is_some() && !self.recursive {
+            return Err(CommandError::IsADirectory(source.to_string()));
+        }
+
+        let (target, name) = super::resolve_destination(ctx, destination, &source)?;
+
+        if source.is_ancestor_of(&target) {
+            return Err(CommandError::InvalidOperation(format!["Cannot copy {} into itself", source]));
+        }
+
+        let copy = source.deep_clone();
+        copy.set_name(&name)?;
+        ctx.add_node(target, copy)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn folders_need_recursive() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let user = ctx.current_dir();
+
+        assert_eq!(run(&ctx, "cp music photos"), Err(CommandError::IsADirectory("/home/test_user/music".to_string())));
+        assert!(user.child("photos").unwrap().child("music").is_none());
+    }
+
+    #[test]
+    fn not_into_itself() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let photos = ctx.current_dir().child("photos").unwrap();
+
+        assert!(matches!(run(&ctx, "cp -r photos photos/japan2026"), Err(CommandError::InvalidOperation(..))));
+        assert!(matches!(run(&ctx, "cp -r photos photos"), Err(CommandError::InvalidOperation(..))));
+        assert!(photos.child("photos").is_none());
+        assert!(photos.child("japan2026").unwrap().child("photos").is_none());
+    }
+
+    #[test]
+    fn never_overwrites() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        assert_eq!(
+            run(&ctx, "cp documents/cv.pdf documents/data.dat"),
+            Err(CommandError::AlreadyExists("/home/test_user/documents/data.dat".to_string())),
+        );
+
+        run(&ctx, "cp -r music downloads").unwrap();
+        assert_eq!(
+            run(&ctx, "cp -r music downloads"),
+            Err(CommandError::AlreadyExists("/home/test_user/downloads/music".to_string())),
+        );
+    }
+
+    #[test]
+    fn sizes_update() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let user = ctx.current_dir();
+
+        run(&ctx, "cp -r music photos/japan2026/tracks").unwrap();
+        let photos = user.child("photos").unwrap();
+        assert_eq!(photos.size(), Some(10));
+        assert_eq!(photos.child("japan2026").unwrap().size(), Some(10));
+        assert_eq!(user.size(), Some(22));
+        assert!(ctx.verify_sizes().is_ok());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use super::format::format_size;
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument};
+
+#[derive(Debug)]
+pub struct DfCmd {
+    human: bool,
+}
+
+impl super::Command for DfCmd {
+    /// Build a DfCmd.
+    /// Takes in an array of arguments. The only argument is `-h`, which shows sizes in larger units.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        let mut human = false;
+
+        for argument in arguments.iter() {
+            match argument {
+                Argument::Flag(flag) if flag == "-h" => human = true,
+                Argument::Flag(_) => return Err(SyntaxError::InvalidFlag),
+                _ => return Err(SyntaxError::InvalidArguments),
+            }
+        }
+
+        Ok(Self { human })
+    }
+
+    /// Execute the df command. This shows the capacity of the tree and how much of it is used
+    /// and free. A tree with no capacity shows `-` for the values that depend on it.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let used = ctx.used_space();
+        let capacity = ctx.capacity();
+
+        let size = |kilobytes: usize| format_size(kilobytes, self.human);
+        let columns = [
+            ("Size", capacity.map_or("-".to_string(), size)),
+            ("Used", size(used)),
+            ("Avail", capacity.map_or("-".to_string(), |capacity| size(capacity.saturating_sub(used)))),
+            ("Use%", capacity.map_or("-".to_string(), |capacity| format!["{}%", percent(used, capacity)])),
+        ];
+
+        // right align each value under its heading.
+        let mut heading = Vec::new();
+        let mut values = Vec::new();
+        for (name, value) in columns.iter() {
+            let width = name.len().max(value.len());
+            heading.push(format!["{:>width$}", name]);
+            values.push(format!["{:>width$}", value]);
+        }
+
+        let mut output = CommandOutput::new();
+        output.push(heading.join("  "));
+        output.push(values.join("  "));
+        Ok(output)
+    }
+}
+
+/// Get how much of `capacity` is `used` as a percentage, rounding up so that any use shows.
+fn percent(used: usize, capacity: usize) -> usize {
+    if capacity == 0 {
+        return 100;
+    }
+    (used * 100).div_ceil(capacity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn capacity_and_usage() {
+        let ctx = Rc::new(build_tree("test_user"));
+        assert_eq!(run(&ctx, "df").unwrap(), ["Size  Used  Avail  Use%", "   -  12KB      -     -"]);
+
+        ctx.set_capacity(Some(16));
+        assert_eq!(run(&ctx, "df").unwrap(), ["Size  Used  Avail  Use%", "16KB  12KB    4KB   75%"]);
+        assert_eq!(percent(1, 3), 34);
+    }
+
+    #[test]
+    fn writes_fail_when_full() {
+        let ctx = Rc::new(build_tree("test_user"));
+        ctx.set_capacity(Some(16));
+
+        assert_eq!(run(&ctx, "touch a.txt 5"), Err(CommandError::NoSpace));
+        assert!(run(&ctx, "touch a.txt 4").is_ok());
+        assert_eq!(run(&ctx, "touch a.txt 5"), Err(CommandError::NoSpace));
+        assert_eq!(run(&ctx, "cp documents/cv.pdf cv.pdf"), Err(CommandError::NoSpace));
+
+        // moving and removing do not need any space.
+        assert!(run(&ctx, "mv a.txt documents && rm documents/a.txt && touch b.txt 4").is_ok());
+        assert_eq!(ctx.used_space(), 16);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument};
+use crate::snapshot;
+
+#[derive(Debug)]
+pub struct DiffCmd {
+    before: String,
+    after: String,
+}
+
+impl super::Command for DiffCmd {
+    /// Build a DiffCmd.
+    /// Takes in an array of arguments. The arguments are the names of the two snapshots to compare.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        // validate argument count
+        if arguments.len() != 2 {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        Ok(Self {
+            before: super::name_from_argument(&arguments[0])?,
+            after: super::name_from_argument(&arguments[1])?,
+        })
+    }
+
+    /// Execute the diff command. This lists the files and folders that were added, removed
+    /// or resized between two snapshots.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let find = |name: &String| {
+            ctx.snapshot(name).ok_or(CommandError::NotFound(format!["snapshot {}", name]))
+        };
+
+        let before = find(&self.before)?;
+        let after = find(&self.after)?;
+
+        let mut output = CommandOutput::new();
+        for change in snapshot::diff(before.root(), after.root()) {
+            output.push(change.to_string());
+        }
+
+        Ok(output)
+    }
+}
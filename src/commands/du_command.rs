@@ -0,0 +1,138 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use super::format::format_size;
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
+use crate::tree::Node;
+
+#[derive(Debug)]
+pub struct DuCmd {
+    paths: Vec<NodePath>,
+
+    /// Whether to only show the total of each path.
+    summary: bool,
+
+    /// The deepest level below each path to show a total for, where its folders have a level of 1.
+    max_depth: Option<usize>,
+
+    human: bool,
+}
+
+impl super::Command for DuCmd {
+    /// Build a DuCmd.
+    /// Takes in an array of arguments. Any number of paths can be given, which default to the
+    /// current directory. `-s` only shows the totals, `-d N` limits the depth and `-h` shows
+    /// sizes in larger units.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        let mut command = Self {
+            paths: Vec::new(),
+            summary: false,
+            max_depth: None,
+            human: false,
+        };
+
+        let mut arguments = arguments.iter();
+        while let Some(argument) = arguments.next() {
+            match argument {
+                Argument::Flag(flag) if flag == "-d" => {
+                    // the depth must be followed by a number
+                    let Some(Argument::Number(n)) = arguments.next() else {
+                        return Err(SyntaxError::InvalidArguments);
+                    };
+                    command.max_depth = Some(*n);
+                },
+                Argument::Flag(flag) => {
+                    for letter in super::flag_letters(flag)? {
+                        match letter {
+                            's' => command.summary = true,
+                            'h' => command.human = true,
+                            _ => return Err(SyntaxError::InvalidFlag),
+                        }
+                    }
+                },
+                Argument::Path(path) => {
+                    // make sure that the path is not the tree root
+                    if let Some(NodePathSegment::Root) | None = path.last() {
+                        return Err(SyntaxError::InvalidType);
+                    }
+                    command.paths.push(path.clone());
+                },
+                _ => return Err(SyntaxError::InvalidType),
+            }
+        }
+
+        Ok(command)
+    }
+
+    /// Execute the du command. This shows the total size of each folder below the paths,
+    /// with each folder listed after the folders inside it.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let targets = if self.paths.is_empty() {
+            vec![ctx.current_dir()]
+        } else {
+            super::expand_all(&ctx, &self.paths)?.iter()
+                .map(|path| ctx.find_node(path))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        // `-s` is the same as a depth of 0.
+        let max_depth = if self.summary { Some(0) } else { self.max_depth };
+
+        let mut totals = Vec::new();
+        for target in targets.iter() {
+            Self::collect(target, target.depth(), max_depth, &mut totals);
+        }
+
+        let sizes: Vec<String> = totals.iter().map(|node| format_size(node.size().unwrap(), self.human)).collect();
+        let width = sizes.iter().map(|size| size.len()).max().unwrap_or(0);
+
+        let mut output = CommandOutput::new();
+        for (node, size) in totals.iter().zip(sizes) {
+            output.push(format!["{:>width$}  {}", size, node]);
+        }
+
+        Ok(output)
+    }
+}
+
+impl DuCmd {
+    /// Add every folder below `node` that is within `max_depth` to `totals`, followed by `node`.
+    /// `start_depth` is the depth of the path the folders are below.
+    fn collect(node: &Rc<Node>, start_depth: usize, max_depth: Option<usize>, totals: &mut Vec<Rc<Node>>) {
+        let mut children = node.child_nodes();
+        children.sort_by_key(|child| child.name());
+
+        for child in children.iter().filter(|child| child.children().is_some()) {
+            if max_depth.is_none_or(|max_depth| child.depth() - start_depth <= max_depth) {
+                Self::collect(child, start_depth, max_depth, totals);
+            }
+        }
+
+        totals.push(Rc::clone(node));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn folder_totals() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        assert_eq!(run(&ctx, "du photos music").unwrap(), [
+            " 0KB  /home/test_user/photos/japan2026",
+            " 0KB  /home/test_user/photos",
+            "10KB  /home/test_user/music",
+        ]);
+        assert_eq!(run(&ctx, "du -s").unwrap(), ["12KB  /home/test_user"]);
+        assert_eq!(run(&ctx, "du -d 1").unwrap().len(), 5);
+        assert_eq!(run(&ctx, "du documents/cv.pdf").unwrap(), ["1KB  /home/test_user/documents/cv.pdf"]);
+
+        ctx.add_node(ctx.current_dir(), Rc::new(Node::new_file("big.iso", 1536))).unwrap();
+        assert_eq!(run(&ctx, "du -sh").unwrap(), ["1.5MB  /home/test_user"]);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument, NodePath, path_to_string};
+use crate::export::{self, ExportError, ExportOptions};
+
+#[derive(Debug)]
+pub struct ExportCmd {
+    path: NodePath,
+    host_path: PathBuf,
+    options: ExportOptions,
+}
+
+impl super::Command for ExportCmd {
+    /// Build an ExportCmd.
+    /// Takes in an array of arguments. The first path is the file or folder in the tree and
+    /// the second is the folder on the host filesystem to export it into.
+    /// `-n` or `--dry-run` lists what would be created without writing anything.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        let mut options = ExportOptions::default();
+        let mut paths = Vec::new();
+
+        for argument in arguments.iter() {
+            match argument {
+                Argument::Flag(flag) => match flag.as_str() {
+                    "-n" | "--dry-run" => options.dry_run = true,
+                    _ => return Err(SyntaxError::InvalidFlag),
+                },
+                Argument::Path(path) => paths.push(path),
+                _ => return Err(SyntaxError::InvalidType),
+            }
+        }
+
+        // validate path count
+        if paths.len() != 2 {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        Ok(Self {
+            path: paths[0].clone(),
+            host_path: PathBuf::from(path_to_string(paths[1])),
+            options,
+        })
+    }
+
+    /// Execute the export command. This writes a file or folder from the tree onto the host
+    /// filesystem.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let path = super::single(super::expand(&ctx, &self.path)?, &self.path)?;
+        let entries = export::export_tree(&ctx, &path, &self.host_path, &self.options)
+            .map_err(|e| match e {
+                ExportError::InvalidSource => CommandError::NotFound(path_to_string(&path)),
+                ExportError::OutsideTarget(name) => CommandError::InvalidName(format!["{} would write outside of the target folder", name]),
+                e => CommandError::Io(format!["Could not export: {}", e]),
+            })?;
+
+        let mut output = CommandOutput::new();
+        let action = if self.options.dry_run { "Would create" } else { "Created" };
+        for entry in entries.iter() {
+            output.push(format!["{} {}", action, entry]);
+        }
+
+        Ok(output)
+    }
+}
@@ -0,0 +1,398 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::glob;
+use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
+use crate::tree::Node;
+
+#[derive(Debug, PartialEq)]
+/// A test that each node found is checked against.
+enum Predicate {
+    /// The name matches a pattern, such as `*.mp3`.
+    Name(String),
+
+    /// The node is a folder if true, or a file if false.
+    Folder(bool),
+
+    /// The size in kilobytes compares to a size in this way, such as `Ordering::Greater`
+    /// for a node larger than the size.
+    Size(Ordering, usize),
+
+    Not(Box<Predicate>),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+
+    /// Matches every node.
+    True,
+}
+
+#[derive(Debug)]
+pub struct FindCmd {
+    paths: Vec<NodePath>,
+    predicate: Predicate,
+
+    /// The deepest level below each path to search, where the path itself has a level of 0.
+    max_depth: Option<usize>,
+
+    /// The shallowest level at which nodes are checked.
+    min_depth: usize,
+
+    /// Whether to remove the nodes found instead of listing them.
+    delete: bool,
+}
+
+/// Parses the expression after the paths of a find command.
+/// ```text
+/// or      := and ("-or" and)*
+/// and     := not (["-and"] not)*
+/// not     := "-not" not | primary
+/// primary := "-name" PATTERN | "-type" ("f" | "d") | "-size" SIZE
+/// ```
+/// `-maxdepth`, `-mindepth` and `-delete` are options rather than tests, so they are taken out
+/// before the expression is parsed.
+struct ExpressionParser<'a> {
+    arguments: &'a [Argument],
+    cursor: usize,
+}
+
+impl ExpressionParser<'_> {
+    /// Get the flag at the cursor, if the argument there is a flag.
+    fn peek_flag(&self) -> Option<&str> {
+        match self.arguments.get(self.cursor) {
+            Some(Argument::Flag(flag)) => Some(flag),
+            _ => None,
+        }
+    }
+
+    fn or(&mut self) -> Result<Predicate, SyntaxError> {
+        let mut predicate = self.and()?;
+        while self.peek_flag() == Some("-or") {
+            self.cursor += 1;
+            predicate = Predicate::Or(Box::new(predicate), Box::new(self.and()?));
+        }
+        Ok(predicate)
+    }
+
+    fn and(&mut self) -> Result<Predicate, SyntaxError> {
+        let mut predicate = self.not()?;
+        loop {
+            match self.peek_flag() {
+                Some("-and") => self.cursor += 1,
+                // tests next to each other are joined by an implicit `-and`.
+                Some("-not" | "-name" | "-type" | "-size") => {},
+                _ => break,
+            }
+            predicate = Predicate::And(Box::new(predicate), Box::new(self.not()?));
+        }
+        Ok(predicate)
+    }
+
+    fn not(&mut self) -> Result<Predicate, SyntaxError> {
+        if self.peek_flag() == Some("-not") {
+            self.cursor += 1;
+            return Ok(Predicate::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Predicate, SyntaxError> {
+        let flag = self.peek_flag().ok_or(SyntaxError::InvalidArguments)?.to_string();
+        let value = self.arguments.get(self.cursor + 1).ok_or(SyntaxError::InvalidArguments)?;
+        self.cursor += 2;
+
+        match (flag.as_str(), value) {
+            // the pattern is matched against names, so an unquoted glob is not expanded.
+            ("-name", Argument::Path(path)) => match path.as_slice() {
+                [NodePathSegment::Name(pattern) | NodePathSegment::Pattern(pattern)] => Ok(Predicate::Name(pattern.clone())),
+                _ => Err(SyntaxError::InvalidPath),
+            },
+            ("-type", Argument::Path(path)) => match path.as_slice() {
+                [NodePathSegment::Name(kind)] if kind == "d" => Ok(Predicate::Folder(true)),
+                [NodePathSegment::Name(kind)] if kind == "f" => Ok(Predicate::Folder(false)),
+                _ => Err(SyntaxError::InvalidArguments),
+            },
+            ("-size", value) => parse_size(value),
+            ("-name" | "-type", _) => Err(SyntaxError::InvalidType),
+            _ => Err(SyntaxError::InvalidFlag),
+        }
+    }
+}
+
+/// Parse the value of `-size`: `N` for exactly N, `+N` for more than N and `-N` for less than N.
+/// Sizes are in kilobytes unless they end with `k`, `M` or `G`.
+fn parse_size(value: &Argument) -> Result<Predicate, SyntaxError> {
+    let text = match value {
+        Argument::Number(n) => return Ok(Predicate::Size(Ordering::Equal, *n)),
+        Argument::Flag(flag) => flag.clone(),
+        Argument::Path(path) => match path.as_slice() {
+            [NodePathSegment::Name(name)] => name.clone(),
+            _ => return Err(SyntaxError::InvalidType),
+        },
+    };
+
+    let (ordering, text) = match text.chars().next() {
+        Some('+') => (Ordering::Greater, &text[1..]),
+        Some('-') => (Ordering::Less, &text[1..]),
+        _ => (Ordering::Equal, text.as_str()),
+    };
+
+    let (number, multiplier) = match text.char_indices().last() {
+        Some((i, 'k')) => (&text[..i], 1),
+        Some((i, 'M')) => (&text[..i], 1024),
+        Some((i, 'G')) => (&text[..i], 1024 * 1024),
+        _ => (text, 1),
+    };
+
+    let size = number.parse::<usize>().ok()
+        .and_then(|size| size.checked_mul(multiplier))
+        .ok_or(SyntaxError::InvalidArguments)?;
+    Ok(Predicate::Size(ordering, size))
+}
+
+impl Predicate {
+    /// Check if `node` passes the test.
+    fn matches(&self, node: &Node) -> bool {
+        match self {
+            Predicate::Name(pattern) => node.name().is_some_and(|name| glob::matches(pattern, &name)),
+            Predicate::Folder(folder) => node.children().is_some() == *folder,
+            Predicate::Size(ordering, size) => node.size().is_some_and(|n| n.cmp(size) == *ordering),
+            Predicate::Not(predicate) => !predicate.matches(node),
+            Predicate::And(a, b) => a.matches(node) && b.matches(node),
+            Predicate::Or(a, b) => a.matches(node) || b.matches(node),
+            Predicate::True => true,
+        }
+    }
+}
+
+impl super::Command for FindCmd {
+    /// Build a FindCmd.
+    /// Takes in an array of arguments. The paths to search come first and default to the
+    /// current directory. They are followed by the tests each node must pass, and the
+    /// `-maxdepth N`, `-mindepth N` and `-delete` options, which can be anywhere after the paths.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        let mut paths = Vec::new();
+        let mut arguments = arguments.iter().peekable();
+        while let Some(Argument::Path(path)) = arguments.peek() {
+            // make sure that the path is not the tree root
+            if let Some(NodePathSegment::Root) | None = path.last() {
+                return Err(SyntaxError::InvalidType);
+            }
+            paths.push(path.clone());
+            arguments.next();
+        }
+
+        let mut command = Self {
+            paths,
+            predicate: Predicate::True,
+            max_depth: None,
+            min_depth: 0,
+            delete: false,
+        };
+
+        let mut expression = Vec::new();
+        while let Some(argument) = arguments.next() {
+            match argument {
+                Argument::Flag(flag) if flag == "-maxdepth" || flag == "-mindepth" => {
+                    // these options are followed by a number
+                    let Some(Argument::Number(n)) = arguments.next() else {
+                        return Err(SyntaxError::InvalidArguments);
+                    };
+
+                    if flag == "-maxdepth" {
+                        command.max_depth = Some(*n);
+                    } else {
+                        command.min_depth = *n;
+                    }
+                },
+                Argument::Flag(flag) if flag == "-delete" => command.delete = true,
+                argument => expression.push(argument.clone()),
+            }
+        }
+
+        if !expression.is_empty() {
+            let mut parser = ExpressionParser { arguments: &expression, cursor: 0 };
+            command.predicate = parser.or()?;
+
+            if parser.cursor != expression.len() {
+                return Err(SyntaxError::InvalidArguments);
+            }
+        }
+
+        Ok(command)
+    }
+
+    /// Execute the find command. This lists every node below the paths that passes the tests,
+    /// or removes them with `-delete`.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let targets = if self.paths.is_empty() {
+            vec![ctx.current_dir()]
+        } else {
+            super::expand_all(&ctx, &self.paths)?.iter()
+                .map(|path| ctx.find_node(path))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut found = Vec::new();
+        for target in targets.iter() {
+            self.walk(target, target.depth(), &mut found);
+        }
+
+        if self.delete {
+            Self::delete(&ctx, &found)?;
+            return Ok(CommandOutput::new());
+        }
+
+        let mut output = CommandOutput::new();
+        for node in found.iter() {
+            output.push(node.to_string());
+        }
+        Ok(output)
+    }
+}
+
+impl FindCmd {
+    /// Add `node` and every node below it that passes the tests to `found`, depth first.
+    /// `start_depth` is the depth of the path being searched.
+    fn walk(&self, node: &Rc<Node>, start_depth: usize, found: &mut Vec<Rc<Node>>) {
+        let depth = node.depth() - start_depth;
+        if depth >= self.min_depth && self.predicate.matches(node) {
+            found.push(Rc::clone(node));
+        }
+
+        if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            return;
+        }
+
+        let mut children = node.child_nodes();
+        children.sort_by_key(|child| child.name());
+        for child in children.iter() {
+            self.walk(child, start_depth, found);
+        }
+    }
+
+    /// Remove every node in `found`. Folders can only be removed if everything in them was
+    /// found too. Nothing is removed if any of the nodes cannot be.
+    fn delete(ctx: &Context, found: &[Rc<Node>]) -> Result<(), CommandError> {
+        let current_dir = ctx.current_dir();
+
+        for node in found.iter() {
+            if node.is_ancestor_of(&current_dir) || Rc::ptr_eq(node, &current_dir) {
+                return Err(CommandError::PermissionDenied(format!["{} contains the current directory", node]));
+            }
+
+            let mut below = Vec::new();
+            Self::descendants(node, &mut below);
+            if !below.iter().all(|child| found.iter().any(|other| Rc::ptr_eq(child, other))) {
+                return Err(CommandError::DirectoryNotEmpty(node.to_string()));
+            }
+        }
+
+        // the nodes were found parents first, so remove them in reverse to empty each folder
+        // before removing it.
+        for node in found.iter().rev() {
+            // a node found from overlapping paths, or inside a folder that was removed, is
+            // already gone.
+            if !ctx.root().is_ancestor_of(node) {
+                continue;
+            }
+
+            let parent = node.parent_node().unwrap();
+            ctx.remove_node(parent, &node.name().unwrap()).map_err(CommandError::InvalidOperation)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add every node below `node` to `below`.
+    fn descendants(node: &Rc<Node>, below: &mut Vec<Rc<Node>>) {
+        for child in node.child_nodes() {
+            Self::descendants(&child, below);
+            below.push(child);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use crate::commands::CommandRegistry;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn parse_expressions() {
+        let build = |input: &str| {
+            let tokens = Lexer::new(input).tokenize();
+            Parser::new(tokens, &CommandRegistry::default()).generate_commands().map(|_| ())
+        };
+
+        assert!(build("find music -name \"*.mp3\" -or -not -type d -size +2M").is_ok());
+        assert!(build("find -maxdepth 2 -type f -delete").is_ok());
+        assert!(build("find -type x").is_err());
+        assert!(build("find -name").is_err());
+        assert!(build("find -size big").is_err());
+        assert!(build("find -size +99999999999999G").is_err());
+        assert!(build("find -type f music").is_err());
+
+        assert_eq!(parse_size(&Argument::Flag("-5M".to_string())).unwrap(), Predicate::Size(Ordering::Less, 5 * 1024));
+        assert_eq!(parse_size(&Argument::Number(3)).unwrap(), Predicate::Size(Ordering::Equal, 3));
+    }
+
+    #[test]
+    fn find_nodes() {
+        let ctx = Rc::new(build_tree("test_user"));
+        ctx.resize_node(ctx.find_node(&vec![
+            NodePathSegment::Name("music".to_string()),
+            NodePathSegment::Name("2.mp3".to_string()),
+        ]).unwrap(), 5).unwrap();
+
+        assert_eq!(run(&ctx, "find documents").unwrap(), [
+            "/home/test_user/documents",
+            "/home/test_user/documents/cv.pdf",
+            "/home/test_user/documents/data.dat",
+        ]);
+        assert_eq!(run(&ctx, "find -type d -mindepth 1 -maxdepth 1").unwrap().len(), 4);
+        assert_eq!(run(&ctx, "find -name \"1*.mp3\"").unwrap(), [
+            "/home/test_user/music/1.mp3",
+            "/home/test_user/music/10.mp3",
+        ]);
+        assert_eq!(run(&ctx, "find music -size +1").unwrap(), ["/home/test_user/music", "/home/test_user/music/2.mp3"]);
+        assert_eq!(run(&ctx, "find documents photos -name cv.pdf -or -type d -not -name photos").unwrap(), [
+            "/home/test_user/documents",
+            "/home/test_user/documents/cv.pdf",
+            "/home/test_user/photos/japan2026",
+        ]);
+    }
+
+    #[test]
+    fn delete_nodes() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        run(&ctx, "find music -name \"*.mp3\" -size 1 -delete").unwrap();
+        assert!(ctx.current_dir().child("music").unwrap().child_nodes().is_empty());
+        assert!(ctx.verify_sizes().is_ok());
+
+        // folders with anything left in them are not removed.
+        assert_eq!(run(&ctx, "find documents -name \"*\" -not -name cv.pdf -delete"),
+            Err(CommandError::DirectoryNotEmpty("/home/test_user/documents".to_string())));
+        assert_eq!(run(&ctx, "find documents -delete && ls").unwrap(), ["downloads/ 0KB", "music/ 0KB", "photos/ 0KB"]);
+        assert!(matches!(run(&ctx, "cd photos && find .. -name photos -delete"), Err(CommandError::PermissionDenied(..))));
+    }
+
+    #[test]
+    fn delete_overlapping_paths() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        run(&ctx, "find music music music/1.mp3 -delete").unwrap();
+        assert!(ctx.current_dir().child("music").is_none());
+        assert!(ctx.verify_sizes().is_ok());
+
+        run(&ctx, "undo").unwrap();
+        assert_eq!(ctx.current_dir().child("music").unwrap().child_nodes().len(), 10);
+        assert!(ctx.verify_sizes().is_ok());
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Format a size in kilobytes, as `1536KB`. When `human` is set, which commands do with `-h`,
+/// the size is shown in the largest unit that fits instead, such as `1.5MB`.
+pub fn format_size(kilobytes: usize, human: bool) -> String {
+    if !human {
+        return format!["{}KB", kilobytes];
+    }
+
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    let mut size = kilobytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    // small sizes in the larger units keep one decimal place, such as `1.5MB`.
+    if unit == 0 || size >= 10.0 || size.fract() == 0.0 {
+        format!["{:.0}{}", size, UNITS[unit]]
+    } else {
+        format!["{:.1}{}", size, UNITS[unit]]
+    }
+}
+
+/// Format a time as `YYYY-MM-DD HH:MM` in UTC.
+pub fn format_time(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    let minutes = seconds % 86400 / 60;
+
+    format!["{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, minutes / 60, minutes % 60]
+}
+
+/// Convert a number of days since 1970-01-01 into a year, month and day.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // shift the epoch to 0000-03-01 so leap days fall at the end of each 400 year era.
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(1536, false), "1536KB");
+        assert_eq!(format_size(1023, true), "1023KB");
+        assert_eq!(format_size(1024, true), "1MB");
+        assert_eq!(format_size(1536, true), "1.5MB");
+        assert_eq!(format_size(20 * 1024 + 100, true), "20MB");
+        assert_eq!(format_size(4 * 1024 * 1024, true), "4GB");
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(format_time(UNIX_EPOCH), "1970-01-01 00:00");
+        assert_eq!(format_time(UNIX_EPOCH + Duration::from_secs(951782400 + 3723)), "2000-02-29 01:02");
+        assert_eq!(format_time(UNIX_EPOCH + Duration::from_secs(1792108800)), "2026-10-16 00:00");
+    }
+}
@@ -0,0 +1,78 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument, NodePath, path_to_string};
+use crate::import::{self, ImportError, ImportOptions, SymlinkPolicy};
+
+#[derive(Debug)]
+pub struct ImportCmd {
+    host_path: PathBuf,
+    path: NodePath,
+    options: ImportOptions,
+}
+
+impl super::Command for ImportCmd {
+    /// Build an ImportCmd.
+    /// Takes in an array of arguments. The first path is the folder on the host
+    /// filesystem and the second is the folder in the tree to import it into.
+    /// `-L` follows symbolic links, and `--max-depth N` and `--max-files N` limit the import.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        let mut options = ImportOptions::default();
+        let mut paths = Vec::new();
+
+        let mut arguments = arguments.iter();
+        while let Some(argument) = arguments.next() {
+            match argument {
+                Argument::Flag(flag) => match flag.as_str() {
+                    "-L" => options.symlinks = SymlinkPolicy::Follow,
+                    "--max-depth" | "--max-files" => {
+                        // these flags are followed by a number
+                        let Some(Argument::Number(n)) = arguments.next() else {
+                            return Err(SyntaxError::InvalidArguments);
+                        };
+
+                        if flag == "--max-depth" {
+                            options.max_depth = Some(*n);
+                        } else {
+                            options.max_files = Some(*n);
+                        }
+                    },
+                    _ => return Err(SyntaxError::InvalidFlag),
+                },
+                Argument::Path(path) => paths.push(path),
+                _ => return Err(SyntaxError::InvalidType),
+            }
+        }
+
+        // validate path count
+        if paths.len() != 2 {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        Ok(Self {
+            host_path: PathBuf::from(path_to_string(paths[0])),
+            path: paths[1].clone(),
+            options,
+        })
+    }
+
+    /// Execute the import command. This copies a folder from the host filesystem into the tree.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let path = super::single(super::expand(&ctx, &self.path)?, &self.path)?;
+        let summary = import::import_dir(&ctx, &self.host_path, &path, &self.options)
+            .map_err(|e| match e {
+                ImportError::AlreadyExists(name) => CommandError::AlreadyExists(name),
+                ImportError::InvalidTarget => CommandError::NotADirectory(path_to_string(&path)),
+                ImportError::NoSpace => CommandError::NoSpace,
+                ImportError::InvalidName(..) => CommandError::InvalidName(e.to_string()),
+                ImportError::InvalidSize(..) => CommandError::InvalidSize(e.to_string()),
+                e => CommandError::Io(format!["Could not import: {}", e]),
+            })?;
+
+        let mut output = CommandOutput::new();
+        output.push(format!["Imported {} folders and {} files ({} skipped)", summary.folders, summary.files, summary.skipped]);
+        Ok(output)
+    }
+}
@@ -0,0 +1,43 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument, path_to_string};
+use crate::persist;
+
+#[derive(Debug)]
+pub struct LoadCmd {
+    file: PathBuf,
+}
+
+impl super::Command for LoadCmd {
+    /// Build a LoadCmd.
+    /// Takes in an array of arguments. The only argument is the file on the host
+    /// filesystem to load the tree from.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        // validate argument count
+        if arguments.len() != 1 {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        // validate that the argument is a path
+        match &arguments[0] {
+            Argument::Path(path) => Ok(Self {
+                file: PathBuf::from(path_to_string(path)),
+            }),
+            _ => Err(SyntaxError::InvalidType),
+        }
+    }
+
+    /// Execute the load command. This replaces the whole tree with the one saved in the file,
+    /// along with the policy saved with it.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let loaded = persist::load_from_file(&self.file)
+            .map_err(|e| CommandError::Io(format!["Could not load the tree: {}", e]))?;
+
+        ctx.set_policy(loaded.policy());
+        ctx.replace_tree(loaded);
+        Ok(CommandOutput::new())
+    }
+}
@@ -1,58 +1,262 @@
 use crate::Context;
+use super::{CommandError, CommandOutput};
+use super::format::{format_size, format_time};
+use std::cmp::Ordering;
 use std::fmt::Debug;
 use std::rc::Rc;
 use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
 use crate::tree::Node;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The order entries are listed in.
+enum SortKey {
+    Name,
+
+    /// Largest first.
+    Size,
+
+    /// Most recently modified first.
+    Modified,
+}
+
 #[derive(Debug)]
 pub struct LsCmd {
-    path: NodePath,
+    paths: Vec<NodePath>,
+
+    /// Whether to list the kind, owner, size and modified time of each entry.
+    long: bool,
+
+    /// Whether to list entries whose names start with a dot.
+    all: bool,
+
+    /// Whether to list the contents of every folder below the listed folders.
+    recursive: bool,
+
+    sort: SortKey,
+    reverse: bool,
+
+    human: bool,
 }
 
 impl super::Command for LsCmd {
     /// Build an LsCmd.
     /// Takes in an array of arguments. This function validates the arguments and
     /// returns a SyntaxError if they are invalid.
+    /// Flags can be grouped, such as `-la`, and any number of paths can be listed.
     fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
-        // check that the supplied argument count is correct.
-        if arguments.len() != 1 && arguments.len() != 0 {
-            return Err(SyntaxError::InvalidArguments);
-        }
-
-        // handle the case where a path to list is supplied
-        if arguments.len() == 1 {
-            if let Argument::Path(node_path) = &arguments[0] {
-                // make sure that the path resolves to a folder
-                if let NodePathSegment::File(..) | NodePathSegment::Root = node_path.last().unwrap() {
-                    return Err(SyntaxError::InvalidType);
-                }
-
-                return Ok(Self {
-                    path: node_path.clone(),
-                });
-            } else {
-                return Err(SyntaxError::InvalidType);
+        let mut command = Self {
+            paths: Vec::new(),
+            long: false,
+            all: false,
+            recursive: false,
+            sort: SortKey::Name,
+            reverse: false,
+            human: false,
+        };
+
+        for argument in arguments.iter() {
+            match argument {
+                Argument::Flag(flag) => {
+                    for letter in super::flag_letters(flag)? {
+                        match letter {
+                            'l' => command.long = true,
+                            'a' => command.all = true,
+                            'R' => command.recursive = true,
+                            'S' => command.sort = SortKey::Size,
+                            't' => command.sort = SortKey::Modified,
+                            'r' => command.reverse = true,
+                            'h' => command.human = true,
+                            _ => return Err(SyntaxError::InvalidFlag),
+                        }
+                    }
+                },
+                Argument::Path(path) => {
+                    // make sure that the path is not the tree root
+                    if let Some(NodePathSegment::Root) | None = path.last() {
+                        return Err(SyntaxError::InvalidType);
+                    }
+                    command.paths.push(path.clone());
+                },
+                _ => return Err(SyntaxError::InvalidType),
             }
         }
 
-        Ok(Self {
-            path: Vec::new(),
-        })
+        Ok(command)
     }
 
     /// Execute the ls command, this lists all files and folders in a directory.
-    fn execute(&self, ctx: Rc<Context>) {
-        let target = ctx.node_from_path(&self.path);
-        
-        if let Ok(target) = target {
-            // print the node and its size for each node in the target dir
-            for node in target.children().unwrap().borrow().iter() {
-                let mut slash_buf = "";
-                if let Node::Folder { .. } = **node {
-                    slash_buf = "/";
-                }
-                println!("{}{} {}KB", node.name().unwrap(), slash_buf, node.size().unwrap());
+    /// Files that are listed directly come first, followed by each folder under a heading
+    /// when more than one is listed.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let targets = if self.paths.is_empty() {
+            vec![ctx.current_dir()]
+        } else {
+            super::expand_all(&ctx, &self.paths)?.iter()
+                .map(|path| ctx.find_node(path))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        let (mut folders, mut files): (Vec<_>, Vec<_>) = targets.iter()
+            .cloned()
+            .partition(|node| node.children().is_some());
+        self.sort(&mut files);
+        self.sort(&mut folders);
+
+        let mut output = CommandOutput::new();
+        for line in self.entries(&files) {
+            output.push(line);
+        }
+
+        let headings = targets.len() > 1 || self.recursive;
+        for folder in folders.iter() {
+            self.list_folder(folder, headings, &mut output);
+        }
+
+        Ok(output)
+    }
+}
+
+impl LsCmd {
+    /// List the contents of `folder`, and with `-R` every folder below it.
+    fn list_folder(&self, folder: &Rc<Node>, heading: bool, output: &mut CommandOutput) {
+        if heading {
+            // separate the heading from anything listed before it.
+            if !output.lines().is_empty() {
+                output.push("");
             }
+            output.push(format!["{}:", folder]);
         }
+
+        let mut children: Vec<Rc<Node>> = folder.child_nodes().into_iter()
+            .filter(|child| self.all || !child.name().unwrap().starts_with('.'))
+            .collect();
+        self.sort(&mut children);
+
+        for line in self.entries(&children) {
+            output.push(line);
+        }
+
+        if self.recursive {
+            for child in children.iter().filter(|child| child.children().is_some()) {
+                self.list_folder(child, true, output);
+            }
+        }
+    }
+
+    /// Sort `nodes` by the chosen key. Nodes that are equal by the key are sorted by name.
+    fn sort(&self, nodes: &mut [Rc<Node>]) {
+        nodes.sort_by(|a, b| {
+            let ordering = match self.sort {
+                SortKey::Name => Ordering::Equal,
+                SortKey::Size => b.size().cmp(&a.size()),
+                SortKey::Modified => b.modified().cmp(&a.modified()),
+            };
+            ordering.then_with(|| a.name().cmp(&b.name()))
+        });
+
+        if self.reverse {
+            nodes.reverse();
+        }
+    }
+
+    /// Format a line for each node in `nodes`. Long format lines are aligned into columns.
+    fn entries(&self, nodes: &[Rc<Node>]) -> Vec<String> {
+        let name = |node: &Rc<Node>| match node.children() {
+            Some(_) => format!["{}/", node.name().unwrap()],
+            None => node.name().unwrap(),
+        };
+        let size = |node: &Rc<Node>| format_size(node.size().unwrap(), self.human);
+
+        if !self.long {
+            return nodes.iter().map(|node| format!["{} {}", name(node), size(node)]).collect();
+        }
+
+        let owner_width = nodes.iter().map(|node| owner(node).len()).max().unwrap_or(0);
+        let size_width = nodes.iter().map(|node| size(node).len()).max().unwrap_or(0);
+
+        nodes.iter().map(|node| {
+            let kind = if node.children().is_some() { 'd' } else { '-' };
+            format![
+                "{} {:<owner_width$} {:>size_width$} {} {}",
+                kind,
+                owner(node),
+                size(node),
+                format_time(node.modified().unwrap()),
+                name(node),
+            ]
+        }).collect()
+    }
+}
+
+/// Get the owner of `node`. Everything in a home folder, such as `/home/user1`, belongs to that
+/// user and everything else belongs to `root`.
+fn owner(node: &Node) -> String {
+    match node.path_names().as_slice() {
+        [home, user, ..] if home == "home" => user.clone(),
+        _ => "root".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use std::thread;
+    use std::time::Duration;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn sorted_and_hidden() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let music = ctx.node_from_path(&vec![NodePathSegment::Name("music".to_string())]).unwrap();
+        ctx.resize_node(music.child("5.mp3").unwrap(), 3).unwrap();
+        thread::sleep(Duration::from_millis(2));
+        music.child("5.mp3").unwrap().touch();
+        ctx.add_node(Rc::clone(&music), Rc::new(Node::new_file(".hidden", 2))).unwrap();
+
+        // entries are sorted by name whatever order they were added in.
+        assert_eq!(run(&ctx, "ls music").unwrap()[..3], ["1.mp3 1KB", "10.mp3 1KB", "2.mp3 1KB"]);
+        assert_eq!(run(&ctx, "ls music").unwrap().len(), 10);
+        assert_eq!(run(&ctx, "ls -a music").unwrap()[0], ".hidden 2KB");
+
+        assert_eq!(run(&ctx, "ls -S music").unwrap()[0], "5.mp3 3KB");
+        assert_eq!(run(&ctx, "ls -Sr music").unwrap()[9], "5.mp3 3KB");
+        assert_eq!(run(&ctx, "ls -t music").unwrap()[0], "5.mp3 3KB");
+    }
+
+    #[test]
+    fn several_paths_and_files() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        assert_eq!(run(&ctx, "ls music/1.mp3").unwrap(), ["1.mp3 1KB"]);
+        assert_eq!(run(&ctx, "ls documents photos/japan2026 music/2.mp3").unwrap(), [
+            "2.mp3 1KB",
+            "",
+            "/home/test_user/documents:",
+            "cv.pdf 1KB",
+            "data.dat 1KB",
+            "",
+            "/home/test_user/photos/japan2026:",
+        ]);
+        assert_eq!(run(&ctx, "ls documents missing"), Err(CommandError::NotFound("missing".to_string())));
+    }
+
+    #[test]
+    fn recursive_and_long() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        assert_eq!(run(&ctx, "ls -R photos").unwrap(), [
+            "/home/test_user/photos:",
+            "japan2026/ 0KB",
+            "",
+            "/home/test_user/photos/japan2026:",
+        ]);
+
+        ctx.add_node(ctx.current_dir(), Rc::new(Node::new_file("big.iso", 1536))).unwrap();
+        let lines = run(&ctx, "ls -lh big.iso documents").unwrap();
+        assert!(lines[0].starts_with("- test_user 1.5MB "), "{}", lines[0]);
+        assert!(lines[0].ends_with(" big.iso"));
+        assert!(lines[3].starts_with("- test_user 1KB "), "{}", lines[3]);
+        assert_eq!(owner(&ctx.root().child("home").unwrap()), "root");
     }
 }
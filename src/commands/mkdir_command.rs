@@ -1,55 +1,142 @@
 use crate::Context;
+use super::{CommandError, CommandOutput};
 use std::fmt::Debug;
 use std::rc::Rc;
-use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
+use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment, path_to_string};
 use crate::tree::Node;
 
 #[derive(Debug)]
 pub struct MkdirCmd{
     path: NodePath,
-    dir_name: String,
+
+    /// Create any missing folders in the path.
+    parents: bool,
 }
 
 impl super::Command for MkdirCmd {
     /// Build a MkdirCmd.
     /// Takes in an array of arguments. The function also validates the arguments
     /// and returns a SyntaxError if they are invalid.
+    /// `-p` creates any missing parent folders.
     fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        let mut parents = false;
+        let mut paths = Vec::new();
+
+        for argument in arguments.iter() {
+            match argument {
+                Argument::Flag(flag) => match flag.as_str() {
+                    "-p" | "--parents" => parents = true,
+                    _ => return Err(SyntaxError::InvalidFlag),
+                },
+                Argument::Path(path) => paths.push(path),
+                _ => return Err(SyntaxError::InvalidType),
+            }
+        }
+
         // validate argument count
-        if arguments.len() != 1 {
+        if paths.len() != 1 {
             return Err(SyntaxError::InvalidArguments);
         }
+        let path = paths[0];
 
-        // validate that the argument is a path
-        let path = match &arguments[0] {
-            Argument::Path(path) => path,
-            _ => return Err(SyntaxError::InvalidType),
-        };
-
-        // get the dir name from the path 
-        let dir_name = match path.last().unwrap() {
-            NodePathSegment::Dir(name) => name.clone(),
-            _ => return Err(SyntaxError::InvalidType),
-        };
+        // make sure the path ends with the name of the new dir
+        if let Some(NodePathSegment::Root | NodePathSegment::Parent) | None = path.last() {
+            return Err(SyntaxError::InvalidType);
+        }
 
         Ok(Self {
-            path: path[..path.len() - 1].to_vec(),
-            dir_name,
+            path: path.clone(),
+            parents,
         })
     }
 
     /// Execute the mkdir command. This creates a new directory.
-    fn execute(&self, ctx: Rc<Context>) {
-        // assure the directory name is not over the maximum allowed character count
-        if self.dir_name.len() > 12 {
-            println!("The dir name cannot be over 12 characters");
-            return;
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let path = super::single(super::expand_new(&ctx, &self.path)?, &self.path)?;
+        let Some((NodePathSegment::Name(dir_name), parent)) = path.split_last() else {
+            return Err(CommandError::NotFound(path_to_string(&path)));
+        };
+
+        // find the folder to create the new directory in.
+        let target = if self.parents {
+            Self::create_parents(&ctx, parent)?
+        } else {
+            ctx.node_from_path(&parent.to_vec())?
+        };
+
+        // with -p an existing directory is not an error.
+        if self.parents && target.child(dir_name).is_some_and(|child| child.children().is_some()) {
+            return Ok(CommandOutput::new());
+        }
+
+        Self::create_dir(&ctx, target, dir_name)?;
+        Ok(CommandOutput::new())
+    }
+}
+
+impl MkdirCmd {
+    /// Create a new folder named `dir_name` in `target`.
+    fn create_dir(ctx: &Context, target: Rc<Node>, dir_name: &str) -> Result<Rc<Node>, CommandError> {
+        ctx.policy().naming.check_folder_name(dir_name).map_err(CommandError::InvalidName)?;
+
+        if let Node::Root { .. } = *target {
+            return Err(CommandError::PermissionDenied("cannot create a directory in the root".to_string()));
+        }
+
+        let new_dir = Rc::new(Node::new_folder(dir_name));
+        ctx.add_node(target, Rc::clone(&new_dir))?;
+        Ok(new_dir)
+    }
+
+    /// Follow `path`, creating each folder that does not exist yet.
+    /// Returns the last folder in the path.
+    fn create_parents(ctx: &Context, path: &[NodePathSegment]) -> Result<Rc<Node>, CommandError> {
+        let mut current = ctx.current_dir();
+
+        for segment in path.iter() {
+            current = match segment {
+                NodePathSegment::Root => ctx.root(),
+                NodePathSegment::Parent => current.parent_node().ok_or(CommandError::NotFound("..".to_string()))?,
+                NodePathSegment::Name(name) => match current.child(name) {
+                    Some(child) if child.children().is_some() => child,
+                    Some(child) => return Err(CommandError::NotADirectory(child.to_string())),
+                    None => Self::create_dir(ctx, current, name)?,
+                },
+                NodePathSegment::Pattern(pattern) => return Err(CommandError::NotFound(pattern.clone())),
+            };
         }
 
-        // create the new directory.
-        if let Ok(target) = ctx.node_from_path(&self.path) {
-            let new_dir = Rc::new(Node::new_folder(&self.dir_name));
-            target.add(new_dir).unwrap();
+        Ok(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn parents_creates_every_folder() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        run(&ctx, "mkdir -p projects/work/q4/reports").unwrap();
+        let mut folder = ctx.current_dir();
+        for name in ["projects", "work", "q4", "reports"] {
+            folder = folder.child(name).unwrap();
+            assert!(folder.children().is_some());
         }
+
+        // running it again changes nothing.
+        run(&ctx, "mkdir -p projects/work/q4/reports").unwrap();
+    }
+
+    #[test]
+    fn missing_parent_is_named() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        assert_eq!(run(&ctx, "mkdir projects/work/q4"), Err(CommandError::NotFound("projects".to_string())));
+        assert_eq!(run(&ctx, "mkdir photos/japan2026/day/raw"), Err(CommandError::NotFound("day".to_string())));
+        assert!(ctx.current_dir().child("projects").is_none());
     }
 }
@@ -1,5 +1,7 @@
 use crate::Context;
-use crate::parser::{Argument, SyntaxError};
+use crate::glob;
+use crate::parser::{Argument, CommandChain, NodePath, NodePathSegment, SyntaxError, is_glob_path, path_to_string};
+use crate::tree::Node;
 use std::fmt::Debug;
 use std::rc::Rc;
 mod cd_command;
@@ -8,57 +10,392 @@ mod touch_command;
 mod mkdir_command;
 mod rm_command;
 mod rmdir_command;
+mod save_command;
+mod load_command;
+mod import_command;
+mod export_command;
+mod undo_command;
+mod redo_command;
+mod snapshot_command;
+mod snapshots_command;
+mod restore_command;
+mod diff_command;
+mod cp_command;
+mod mv_command;
+mod tree_command;
+mod du_command;
+mod df_command;
+mod find_command;
+mod format;
+mod output;
+mod registry;
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-/// Represents a type of command
-pub enum CommandType {
-    Cd,
-    Ls,
-    Touch,
-    Mkdir,
-    Rm,
-    Rmdir,
-}
+pub use output::{CommandError, CommandOutput, ExitStatus};
+pub use registry::{BuildFn, CommandRegistry};
 
 /// CommandBuilder is used for building a command.
 pub struct CommandBuilder {
-    /// The type of the command to build.
-    command_type: CommandType,
+    /// The name of the command to build.
+    name: String,
 
     /// The arguments supplied into the command.
     arguments: Vec<Argument>,
 }
 
 impl CommandBuilder {
-    /// Create a new CommandBuilder instance with a provided command type.
-    pub fn new(command_type: CommandType) -> Self {
+    /// Create a new CommandBuilder instance for the command called `name`.
+    pub fn new(name: &str) -> Self {
         Self {
-            command_type,
+            name: name.to_string(),
             arguments: Vec::new(),
         }
     }
 
+    /// Get the name of the command being built.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Add an argument to the command
     pub fn add_argument(&mut self, arg: Argument) {
         self.arguments.push(arg);
     }
 
-    /// Build the final command. Uses the arguments previously provided with
-    /// the add_argument associated function.
-    pub fn build(&self) -> Result<Box<dyn Command>, SyntaxError> {
-        match self.command_type {
-            CommandType::Cd => Ok(Box::new(cd_command::CdCmd::build(&self.arguments)?)),
-            CommandType::Ls => Ok(Box::new(ls_command::LsCmd::build(&self.arguments)?)),
-            CommandType::Touch => Ok(Box::new(touch_command::TouchCmd::build(&self.arguments)?)),
-            CommandType::Mkdir => Ok(Box::new(mkdir_command::MkdirCmd::build(&self.arguments)?)),
-            CommandType::Rm => Ok(Box::new(rm_command::RmCmd::build(&self.arguments)?)),
-            CommandType::Rmdir => Ok(Box::new(rmdir_command::RmdirCmd::build(&self.arguments)?)),
+    /// Build the final command by looking up its name in `registry`. Uses the arguments
+    /// previously provided with the add_argument associated function.
+    pub fn build(&self, registry: &CommandRegistry) -> Result<Box<dyn Command>, SyntaxError> {
+        registry.build(&self.name, &self.arguments)
+    }
+}
+
+/// Get a plain name, such as the name of a snapshot, from an argument.
+/// The argument must be a path with a single segment.
+fn name_from_argument(argument: &Argument) -> Result<String, SyntaxError> {
+    match argument {
+        Argument::Path(path) => match path.as_slice() {
+            [NodePathSegment::Name(name)] => Ok(name.clone()),
+            _ => Err(SyntaxError::InvalidPath),
+        },
+        _ => Err(SyntaxError::InvalidType),
+    }
+}
+
+/// Split a group of single letter flags such as `-rf` into its letters.
+fn flag_letters(flag: &str) -> Result<Vec<char>, SyntaxError> {
+    match flag.strip_prefix('-') {
+        Some(letters) if !letters.is_empty() && !letters.starts_with('-') => Ok(letters.chars().collect()),
+        _ => Err(SyntaxError::InvalidFlag),
+    }
+}
+
+/// Expand the globs in `path` against the tree as it is when the command runs, so that a glob
+/// sees the changes made by the commands before it on the same line. A path without globs is
+/// kept as it is, and a glob that matches nothing is an error.
+fn expand(ctx: &Context, path: &NodePath) -> Result<Vec<NodePath>, CommandError> {
+    if !is_glob_path(path) {
+        return Ok(vec![path.clone()]);
+    }
+
+    let paths = glob::expand_path(ctx, path);
+    if paths.is_empty() {
+        return Err(CommandError::NoMatches(path_to_string(path)));
+    }
+
+    Ok(paths)
+}
+
+/// Expand the globs in each of `paths`, keeping the order they were given in.
+fn expand_all(ctx: &Context, paths: &[NodePath]) -> Result<Vec<NodePath>, CommandError> {
+    let mut expanded = Vec::new();
+    for path in paths.iter() {
+        expanded.extend(expand(ctx, path)?);
+    }
+
+    Ok(expanded)
+}
+
+/// Expand the globs in `path` for a command that creates the node it names, such as `touch`.
+/// Only the folders before the last segment need to exist, unless the last segment is a glob.
+fn expand_new(ctx: &Context, path: &NodePath) -> Result<Vec<NodePath>, CommandError> {
+    match path.split_last() {
+        Some((last @ NodePathSegment::Name(..), folder)) if is_glob_path(folder) => {
+            let mut paths = expand(ctx, &folder.to_vec())?;
+            paths.iter_mut().for_each(|path| path.push(last.clone()));
+            Ok(paths)
+        },
+        _ => expand(ctx, path),
+    }
+}
+
+/// Get the only path in `paths`, which were expanded from `path`.
+fn single(paths: Vec<NodePath>, path: &NodePath) -> Result<NodePath, CommandError> {
+    match <[NodePath; 1]>::try_from(paths) {
+        Ok([path]) => Ok(path),
+        Err(_) => Err(CommandError::TooManyMatches(path_to_string(path))),
+    }
+}
+
+/// Check that `destination` is an existing folder, which it must be when several nodes are
+/// copied or moved to it at once.
+fn check_destination_folder(ctx: &Context, destination: &NodePath) -> Result<(), CommandError> {
+    let folder = ctx.find_node(destination)?;
+    if folder.children().is_none() {
+        return Err(CommandError::NotADirectory(folder.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Find where `source` should be placed for a command that copies or moves it to `destination`.
+/// If the destination is an existing folder, the source keeps its name and goes inside it.
+/// Otherwise the last part of the destination is the new name in its parent folder.
+/// Returns the folder and the name, or an error if the destination is not valid.
+fn resolve_destination(ctx: &Context, destination: &NodePath, source: &Rc<Node>) -> Result<(Rc<Node>, String), CommandError> {
+    let (target, name) = match ctx.find_node(destination) {
+        Ok(existing) if existing.children().is_some() => (existing, source.name().unwrap()),
+        Ok(existing) => return Err(CommandError::AlreadyExists(existing.to_string())),
+        Err(e) => {
+            let name = match destination.last() {
+                Some(NodePathSegment::Name(name)) => name.clone(),
+                _ => return Err(e.into()),
+            };
+
+            let target = ctx.node_from_path(&destination[..destination.len() - 1].to_vec())?;
+            (target, name)
+        },
+    };
+
+    if let Node::Root { .. } = *target {
+        return Err(CommandError::PermissionDenied("cannot place items in the root".to_string()));
+    }
+
+    // a node that keeps its name is not checked, as it may be older than the policy.
+    if Some(&name) != source.name().as_ref() {
+        let naming = ctx.policy().naming;
+        match **source {
+            Node::File { .. } => naming.check_file_name(&name),
+            _ => naming.check_folder_name(&name),
+        }.map_err(CommandError::InvalidName)?;
+    }
+
+    if let Some(existing) = target.child(&name) {
+        // moving a node onto itself is not a collision.
+        if !Rc::ptr_eq(&existing, source) {
+            return Err(CommandError::AlreadyExists(existing.to_string()));
         }
     }
+
+    Ok((target, name))
+}
+
+/// Execute a chain of commands in order, passing the result of each command that runs to
+/// `report`. A command is skipped if its connector does not match the status of the last
+/// command that ran. Everything a command changes is undone together.
+/// Returns the exit status of the last command that ran.
+pub fn execute_chain(
+    ctx: &Rc<Context>,
+    commands: &CommandChain,
+    mut report: impl FnMut(Result<CommandOutput, CommandError>),
+) -> ExitStatus {
+    let mut status = ExitStatus::Success;
+
+    for (connector, command) in commands.iter() {
+        if !connector.should_run(status) {
+            continue;
+        }
+
+        ctx.begin_change();
+        let result = command.execute(Rc::clone(ctx));
+        ctx.commit_change();
+        status = ExitStatus::of(&result);
+        report(result);
+    }
+
+    status
 }
 
 /// Represents any command.
 pub trait Command : Debug {
     fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> where Self: Sized;
-    fn execute(&self, ctx: Rc<Context>);
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::{Connector, Parser};
+    use crate::policy::{ExtensionRule, Policy};
+    use crate::tree::build_tree;
+
+    /// Run a single line of input against `ctx`, returning the lines printed by the last
+    /// command that ran or its error. The tests of each command use this as well.
+    pub(super) fn run(ctx: &Rc<Context>, input: &str) -> Result<Vec<String>, CommandError> {
+        let tokens = Lexer::new(input).tokenize();
+        let commands = Parser::new(tokens, &CommandRegistry::default()).generate_commands().unwrap();
+        let mut last = Ok(Vec::new());
+        execute_chain(ctx, &commands, |result| last = result.map(|output| output.lines().to_vec()));
+        last
+    }
+
+    #[test]
+    fn commands_return_output() {
+        let ctx = Rc::new(build_tree("test_user"));
+        run(&ctx, "cd documents").unwrap();
+
+        let output = run(&ctx, "ls").unwrap();
+        assert_eq!(output, ["cv.pdf 1KB", "data.dat 1KB"]);
+        assert!(run(&ctx, "touch new.txt 4").unwrap().is_empty());
+    }
+
+    #[test]
+    fn commands_return_errors() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        assert_eq!(run(&ctx, "cd missing"), Err(CommandError::NotFound("missing".to_string())));
+        assert_eq!(run(&ctx, "rm music"), Err(CommandError::IsADirectory("/home/test_user/music".to_string())));
+        assert!(matches!(run(&ctx, "touch ab.txt 0"), Err(CommandError::InvalidSize(..))));
+        assert!(matches!(run(&ctx, "mkdir averylongname"), Err(CommandError::InvalidName(..))));
+        assert!(run(&ctx, "rm -f missing.txt").is_ok());
+    }
+
+    #[test]
+    fn undo_whole_commands() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let user = ctx.current_dir();
+        let names = || user.children().unwrap().borrow().iter().map(|child| child.name().unwrap()).collect::<Vec<_>>();
+        let original = names();
+
+        for line in ["mkdir -p a/b/c", "rm -r music", "cp -r documents copies"] {
+            run(&ctx, line).unwrap();
+            run(&ctx, "undo").unwrap();
+            assert_eq!(names(), original, "{}", line);
+        }
+
+        run(&ctx, "rm -r documents && undo").unwrap();
+        assert_eq!(user.child("documents").unwrap().children().unwrap().borrow().len(), 2);
+
+        // redo also applies the whole command.
+        run(&ctx, "redo").unwrap();
+        assert!(user.child("documents").is_none());
+        assert!(ctx.verify_sizes().is_ok());
+    }
+
+    #[test]
+    fn names_with_any_dots() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        run(&ctx, "touch Makefile && touch .bashrc && touch a.tar.gz && mkdir .config").unwrap();
+        let current_dir = ctx.current_dir();
+        assert!(matches!(*current_dir.child("Makefile").unwrap(), Node::File { .. }));
+        assert!(matches!(*current_dir.child(".bashrc").unwrap(), Node::File { .. }));
+        assert!(matches!(*current_dir.child("a.tar.gz").unwrap(), Node::File { .. }));
+
+        // whether a name is a folder comes from the tree, not from its dots.
+        run(&ctx, "cd .config && touch v1.2 && cd ..").unwrap();
+        assert_eq!(run(&ctx, "ls .config").unwrap(), ["v1.2 1KB"]);
+        assert_eq!(run(&ctx, "cd Makefile"), Err(CommandError::NotADirectory("/home/test_user/Makefile".to_string())));
+        assert!(run(&ctx, "rm a.tar.gz").is_ok());
+    }
+
+    #[test]
+    fn extension_length_policy() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let mut policy = Policy::default();
+        policy.naming.extension = ExtensionRule::Exactly(3);
+        ctx.set_policy(policy);
+
+        assert!(run(&ctx, "touch a.txt && touch .bashrc.old").is_ok());
+        assert!(matches!(run(&ctx, "touch Makefile"), Err(CommandError::InvalidName(..))));
+        assert!(matches!(run(&ctx, "touch .bashrc"), Err(CommandError::InvalidName(..))));
+        assert!(matches!(run(&ctx, "touch a.tar.gz"), Err(CommandError::InvalidName(..))));
+
+        ctx.set_policy(Policy::default());
+        assert!(run(&ctx, "touch Makefile").is_ok());
+    }
+
+    #[test]
+    fn policy_applies_to_every_command() {
+        let ctx = Rc::new(build_tree("test_user"));
+        ctx.set_policy(Policy::dos83());
+
+        assert!(matches!(run(&ctx, "mkdir -p a/.config"), Err(CommandError::InvalidName(..))));
+        assert!(matches!(run(&ctx, "cp documents/cv.pdf cv.html"), Err(CommandError::InvalidName(..))));
+        assert!(matches!(run(&ctx, "mv music longfoldername"), Err(CommandError::InvalidName(..))));
+        assert!(run(&ctx, "touch empty.txt 0 && mv documents backup && mv backup/cv.pdf cv.doc").is_ok());
+
+        ctx.set_policy(Policy::permissive());
+        assert!(run(&ctx, "mkdir averylongname && touch \"a b.c\" 8000000").is_ok());
+    }
+
+    #[test]
+    fn globs_give_several_paths() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        assert_eq!(run(&ctx, "ls music/1*").unwrap(), ["1.mp3 1KB", "10.mp3 1KB"]);
+        run(&ctx, "rm music/?.mp3 && touch a.txt b.txt .c.txt 2").unwrap();
+        assert_eq!(ctx.current_dir().child("music").unwrap().child_nodes().len(), 1);
+        assert_eq!(ctx.current_dir().child("b.txt").unwrap().size(), Some(2));
+
+        // hidden files are only matched by a glob starting with a dot.
+        run(&ctx, "mv *.txt documents").unwrap();
+        assert!(ctx.current_dir().child(".c.txt").is_some());
+        assert_eq!(run(&ctx, "cp documents/*.txt music/10.mp3"), Err(CommandError::NotADirectory("/home/test_user/music/10.mp3".to_string())));
+        assert!(run(&ctx, "cp documents/c* .c.txt photos").is_ok());
+        assert_eq!(ctx.current_dir().child("photos").unwrap().child_nodes().len(), 3);
+
+        // a name given twice, or inside a folder that is removed, is removed once.
+        assert!(run(&ctx, "rm -r documents documents/a.txt photos/cv.pdf photos/cv.pdf").is_ok());
+        assert!(ctx.current_dir().child("documents").is_none());
+        assert_eq!(run(&ctx, "rm photos/.c.txt missing.txt"), Err(CommandError::NotFound("missing.txt".to_string())));
+        assert!(ctx.current_dir().child("photos").unwrap().child(".c.txt").is_some());
+    }
+
+    #[test]
+    fn globs_expand_when_each_command_runs() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        // the glob matches in the folder the cd moved to, not where the line started.
+        run(&ctx, "touch a.txt; mkdir sub; touch sub/a.txt sub/b.txt; cd sub && rm *.txt").unwrap();
+        assert!(ctx.current_dir().child_nodes().is_empty());
+        assert!(ctx.current_dir().parent_node().unwrap().child("a.txt").is_some());
+
+        // a glob sees the files made earlier in the line.
+        run(&ctx, "touch x.mp3 && rm *.mp3").unwrap();
+        assert!(ctx.current_dir().child_nodes().is_empty());
+
+        assert_eq!(run(&ctx, "rm *.mp3"), Err(CommandError::NoMatches("*.mp3".to_string())));
+        assert!(run(&ctx, "rm -f *.mp3").is_ok());
+        assert_eq!(run(&ctx, "cd ../*s"), Err(CommandError::TooManyMatches("../*s".to_string())));
+        assert_eq!(run(&ctx, "cd ../p* && find ../music -name *.mp3").unwrap().len(), 10);
+    }
+
+    #[test]
+    fn and_stops_at_failure() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        // the cd fails so nothing is removed from the current directory.
+        assert!(run(&ctx, "cd missing && rm -r music").is_err());
+        assert!(ctx.current_dir().child("music").is_some());
+
+        assert!(run(&ctx, "cd missing && ls || mkdir backup").is_ok());
+        assert!(ctx.current_dir().child("backup").is_some());
+    }
+
+    #[test]
+    fn or_and_semicolon() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        // the mkdir succeeds so the touch after || is skipped, but the one after ; runs.
+        run(&ctx, "mkdir a || touch b.txt; touch c.txt").unwrap();
+        let current_dir = ctx.current_dir();
+        assert!(current_dir.child("a").is_some());
+        assert!(current_dir.child("b.txt").is_none());
+        assert!(current_dir.child("c.txt").is_some());
+
+        let tokens = Lexer::new("undo;redo").tokenize();
+        let commands = Parser::new(tokens, &CommandRegistry::default()).generate_commands().unwrap();
+        let connectors: Vec<Connector> = commands.iter().map(|(connector, _)| *connector).collect();
+        assert_eq!(connectors, [Connector::Always, Connector::Always]);
+    }
 }
@@ -0,0 +1,133 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
+use crate::tree::Node;
+
+#[derive(Debug)]
+pub struct MvCmd {
+    sources: Vec<NodePath>,
+    destination: NodePath,
+}
+
+impl super::Command for MvCmd {
+    /// Build a MvCmd.
+    /// Takes in an array of arguments. The last path is where to move to, or the new name,
+    /// and the paths before it are the files and folders to move.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        // validate argument count
+        if arguments.len() < 2 {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        // validate that every argument is a path
+        let mut paths = Vec::new();
+        for argument in arguments.iter() {
+            let Argument::Path(path) = argument else {
+                return Err(SyntaxError::InvalidType);
+            };
+            paths.push(path.clone());
+        }
+
+        let destination = paths.pop().unwrap();
+
+        // the tree root cannot be moved
+        if paths.iter().any(|source| matches!(source.last(), Some(NodePathSegment::Root) | None)) {
+            return Err(SyntaxError::InvalidPath);
+        }
+
+        Ok(Self {
+            sources: paths,
+            destination,
+        })
+    }
+
+    /// Execute the mv command. This moves each file or folder to a new location or renames it.
+    /// Several sources can only be moved into a folder. Nothing is copied, so each node keeps
+    /// its contents and anything referring to it.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let sources = super::expand_all(&ctx, &self.sources)?;
+        let destination = super::single(super::expand_new(&ctx, &self.destination)?, &self.destination)?;
+        if sources.len() > 1 {
+            super::check_destination_folder(&ctx, &destination)?;
+        }
+
+        for source in sources.iter() {
+            Self::move_to(&ctx, ctx.find_node(source)?, &destination)?;
+        }
+
+        Ok(CommandOutput::new())
+    }
+}
+
+impl MvCmd {
+    /// Move `source` to `destination`.
+    fn move_to(ctx: &Context, source: Rc<Node>, destination: &NodePath) -> Result<(), CommandError> {
+        if let Node::Root { .. } = *source {
+            return Err(CommandError::PermissionDenied("cannot move the root".to_string()));
+        }
+
+        let (target, name) = super::resolve_destination(ctx, destination, &source)?;
+
+        if source.is_ancestor_of(&target) {
+            return Err(CommandError::InvalidOperation(format!["Cannot move {} into itself", source]));
+        }
+
+        ctx.move_node(source, target, &name)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn rename_in_place() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let user = ctx.current_dir();
+
+        run(&ctx, "mv documents/cv.pdf documents/resume.pdf").unwrap();
+        let documents = user.child("documents").unwrap();
+        assert!(documents.child("cv.pdf").is_none());
+        assert!(documents.child("resume.pdf").is_some());
+        assert_eq!(documents.size(), Some(2));
+
+        run(&ctx, "mv music songs").unwrap();
+        assert!(user.child("music").is_none());
+        assert_eq!(user.child("songs").unwrap().size(), Some(10));
+        assert!(ctx.verify_sizes().is_ok());
+    }
+
+    #[test]
+    fn not_into_itself() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let user = ctx.current_dir();
+
+        assert!(matches!(run(&ctx, "mv photos photos/japan2026"), Err(CommandError::InvalidOperation(..))));
+        assert!(matches!(run(&ctx, "mv photos photos"), Err(CommandError::InvalidOperation(..))));
+        assert!(user.child("photos").unwrap().child("japan2026").is_some());
+    }
+
+    #[test]
+    fn never_overwrites() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let user = ctx.current_dir();
+
+        assert_eq!(
+            run(&ctx, "mv documents/cv.pdf documents/data.dat"),
+            Err(CommandError::AlreadyExists("/home/test_user/documents/data.dat".to_string())),
+        );
+
+        run(&ctx, "touch documents/song.mp3 && touch music/song.mp3").unwrap();
+        assert_eq!(
+            run(&ctx, "mv music/song.mp3 documents"),
+            Err(CommandError::AlreadyExists("/home/test_user/documents/song.mp3".to_string())),
+        );
+        assert!(user.child("music").unwrap().child("song.mp3").is_some());
+        assert!(ctx.verify_sizes().is_ok());
+    }
+}
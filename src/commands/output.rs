@@ -0,0 +1,120 @@
+use std::fmt;
+
+use crate::tree::{InvalidFolder, NodeError};
+
+#[derive(Debug, Default, PartialEq)]
+/// The text produced by a command that ran successfully, one entry per line.
+pub struct CommandOutput {
+    lines: Vec<String>,
+}
+
+impl CommandOutput {
+    /// Create an output with no lines.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a line to the output.
+    pub fn push(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+    }
+
+    /// Get the lines of the output.
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+}
+
+impl fmt::Display for CommandOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines.join("\n"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// Whether a command succeeded. This decides which of the commands chained after it run.
+pub enum ExitStatus {
+    Success,
+    Failure,
+}
+
+impl ExitStatus {
+    /// Get the exit status of a finished command.
+    pub fn of(result: &Result<CommandOutput, CommandError>) -> Self {
+        match result {
+            Ok(_) => ExitStatus::Success,
+            Err(_) => ExitStatus::Failure,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// The reason a command failed. Paths are the full paths of the nodes involved, or the name
+/// that was supplied when the node does not exist.
+pub enum CommandError {
+    NotFound(String),
+    NotADirectory(String),
+    IsADirectory(String),
+    AlreadyExists(String),
+    DirectoryNotEmpty(String),
+
+    /// A name that breaks the naming rules, with the rule that was broken.
+    InvalidName(String),
+
+    /// A size that is not allowed, with the reason.
+    InvalidSize(String),
+
+    /// An operation on a protected node, such as the root or the current directory.
+    PermissionDenied(String),
+
+    /// An operation that cannot be done in the current state, such as undoing with no history.
+    InvalidOperation(String),
+
+    /// A failure reading or writing the host filesystem.
+    Io(String),
+
+    /// A change that would make the files in the tree larger than its capacity.
+    NoSpace,
+
+    /// A glob that did not match anything in the tree.
+    NoMatches(String),
+
+    /// A glob that matched several nodes where only one can be used, such as with `cd`.
+    TooManyMatches(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::NotFound(path) => write!(f, "{}: No such file or folder", path),
+            CommandError::NotADirectory(path) => write!(f, "{}: Not a folder", path),
+            CommandError::IsADirectory(path) => write!(f, "{}: Is a folder", path),
+            CommandError::AlreadyExists(path) => write!(f, "{}: Already exists", path),
+            CommandError::DirectoryNotEmpty(path) => write!(f, "{}: Folder not empty", path),
+            CommandError::InvalidName(reason) => write!(f, "Invalid name: {}", reason),
+            CommandError::InvalidSize(reason) => write!(f, "Invalid size: {}", reason),
+            CommandError::PermissionDenied(reason) => write!(f, "Permission denied: {}", reason),
+            CommandError::InvalidOperation(reason) => write!(f, "{}", reason),
+            CommandError::Io(reason) => write!(f, "{}", reason),
+            CommandError::NoSpace => write!(f, "No space left in the tree"),
+            CommandError::NoMatches(pattern) => write!(f, "{}: No matches found", pattern),
+            CommandError::TooManyMatches(pattern) => write!(f, "{}: Matches more than one path", pattern),
+        }
+    }
+}
+
+impl From<InvalidFolder> for CommandError {
+    fn from(e: InvalidFolder) -> Self {
+        CommandError::NotFound(e.0)
+    }
+}
+
+impl From<NodeError> for CommandError {
+    fn from(e: NodeError) -> Self {
+        match e {
+            NodeError::WrongType => CommandError::InvalidOperation(e.to_string()),
+            NodeError::AlreadyExists(name) => CommandError::AlreadyExists(name),
+            NodeError::NoSpace => CommandError::NoSpace,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument};
+
+#[derive(Debug)]
+pub struct RedoCmd;
+
+impl super::Command for RedoCmd {
+    /// Build a RedoCmd. This command takes no arguments.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        if !arguments.is_empty() {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        Ok(Self)
+    }
+
+    /// Execute the redo command. This reapplies the most recently undone change to the tree.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        if !ctx.redo() {
+            return Err(CommandError::InvalidOperation("Nothing to redo".to_string()));
+        }
+
+        Ok(CommandOutput::new())
+    }
+}
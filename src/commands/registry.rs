@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use crate::parser::{Argument, SyntaxError};
+use super::Command;
+use super::{cd_command, cp_command, df_command, diff_command, du_command, find_command, export_command, import_command, load_command, ls_command};
+use super::{mkdir_command, mv_command, redo_command, restore_command, rm_command, rmdir_command, save_command};
+use super::{snapshot_command, snapshots_command, touch_command, tree_command, undo_command};
+
+/// A function that builds a command from the arguments it was given.
+pub type BuildFn = Box<dyn Fn(&[Argument]) -> Result<Box<dyn Command>, SyntaxError>>;
+
+/// A command that can be run by name.
+struct CommandEntry {
+    help: String,
+    build: BuildFn,
+}
+
+/// The commands that can be run, looked up by name when a line is parsed.
+/// `CommandRegistry::default()` has every built in command, `CommandRegistry::new()` has none.
+pub struct CommandRegistry {
+    commands: BTreeMap<String, CommandEntry>,
+}
+
+impl CommandRegistry {
+    /// Create a registry with no commands.
+    pub fn new() -> Self {
+        Self { commands: BTreeMap::new() }
+    }
+
+    /// Register a command called `name` that is built with `build`.
+    /// Replaces any command already registered with the same name.
+    pub fn register(
+        &mut self,
+        name: &str,
+        help: &str,
+        build: impl Fn(&[Argument]) -> Result<Box<dyn Command>, SyntaxError> + 'static,
+    ) {
+        self.commands.insert(name.to_string(), CommandEntry {
+            help: help.to_string(),
+            build: Box::new(build),
+        });
+    }
+
+    /// Register a command called `name` that is built with `C::build`.
+    pub fn register_command<C: Command + 'static>(&mut self, name: &str, help: &str) {
+        self.register(name, help, |arguments| Ok(Box::new(C::build(arguments)?)));
+    }
+
+    /// Remove the command called `name`. Returns false if there was no such command.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.commands.remove(name).is_some()
+    }
+
+    /// Check if a command called `name` is registered.
+    pub fn contains(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    /// Get the help text of the command called `name`.
+    pub fn help(&self, name: &str) -> Option<&str> {
+        self.commands.get(name).map(|entry| entry.help.as_str())
+    }
+
+    /// Get the name and help text of every command, ordered by name.
+    pub fn commands(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.commands.iter().map(|(name, entry)| (name.as_str(), entry.help.as_str()))
+    }
+
+    /// Build the command called `name` with `arguments`.
+    pub fn build(&self, name: &str, arguments: &[Argument]) -> Result<Box<dyn Command>, SyntaxError> {
+        let entry = self.commands.get(name).ok_or(SyntaxError::UnknownCommand(name.to_string()))?;
+        (entry.build)(arguments)
+    }
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+
+        registry.register_command::<cd_command::CdCmd>("cd", "cd <folder>  Change the current directory");
+        registry.register_command::<ls_command::LsCmd>("ls", "ls [-laRStrh] [path...]  List files and the contents of folders");
+        registry.register_command::<touch_command::TouchCmd>("touch", "touch <file>... [size]  Create files or update existing ones");
+        registry.register_command::<mkdir_command::MkdirCmd>("mkdir", "mkdir [-p] <folder>  Create a folder");
+        registry.register_command::<rm_command::RmCmd>("rm", "rm [-rfi] <path>...  Remove files, or folders with -r");
+        registry.register_command::<rmdir_command::RmdirCmd>("rmdir", "rmdir <folder>  Remove an empty folder");
+        registry.register_command::<save_command::SaveCmd>("save", "save <file>  Save the tree to a JSON file");
+        registry.register_command::<load_command::LoadCmd>("load", "load <file>  Replace the tree with one saved to a JSON file");
+        registry.register_command::<import_command::ImportCmd>("import", "import [-L] [--max-depth n] [--max-files n] <host folder> <folder>  Import a folder from the host");
+        registry.register_command::<export_command::ExportCmd>("export", "export [-n] <path> <host folder>  Export part of the tree to the host");
+        registry.register_command::<undo_command::UndoCmd>("undo", "undo  Revert the last change to the tree");
+        registry.register_command::<redo_command::RedoCmd>("redo", "redo  Reapply the last undone change");
+        registry.register_command::<snapshot_command::SnapshotCmd>("snapshot", "snapshot <name>  Save a copy of the tree");
+        registry.register_command::<snapshots_command::SnapshotsCmd>("snapshots", "snapshots  List the saved copies of the tree");
+        registry.register_command::<restore_command::RestoreCmd>("restore", "restore <name>  Replace the tree with a saved copy");
+        registry.register_command::<diff_command::DiffCmd>("diff", "diff <before> <after>  List the changes between two saved copies");
+        registry.register_command::<cp_command::CpCmd>("cp", "cp [-r] <source>... <destination>  Copy files, or folders with -r");
+        registry.register_command::<mv_command::MvCmd>("mv", "mv <source>... <destination>  Move or rename files and folders");
+        registry.register_command::<du_command::DuCmd>("du", "du [-sh] [-d depth] [path...]  Show the total size of each folder");
+        registry.register_command::<df_command::DfCmd>("df", "df [-h]  Show the capacity of the tree and the space used");
+        registry.register_command::<find_command::FindCmd>("find", "find [path...] [-name pattern] [-type f|d] [-size [+-]n] [-maxdepth n] [-mindepth n] [-delete]  Search for files and folders");
+        registry.register_command::<tree_command::TreeCmd>("tree", "tree [-adh] [-L depth] [folder]  Show everything below a folder");
+
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use crate::Context;
+    use crate::commands::{CommandError, CommandOutput};
+    use crate::tree::build_tree;
+
+    #[derive(Debug)]
+    struct CountCmd;
+
+    impl Command for CountCmd {
+        fn build(_: &[Argument]) -> Result<Self, SyntaxError> {
+            Ok(Self)
+        }
+
+        fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+            let mut output = CommandOutput::new();
+            output.push(ctx.current_dir().child_nodes().len().to_string());
+            Ok(output)
+        }
+    }
+
+    #[test]
+    fn register_custom_command() {
+        let mut registry = CommandRegistry::default();
+        registry.register_command::<CountCmd>("count", "count  Count the items in the current directory");
+
+        assert!(registry.contains("count"));
+        assert_eq!(registry.help("count"), Some("count  Count the items in the current directory"));
+
+        let ctx = Rc::new(build_tree("test_user"));
+        let command = registry.build("count", &[]).unwrap();
+        assert_eq!(command.execute(ctx).unwrap().lines(), ["4"]);
+
+        assert!(registry.unregister("count"));
+        assert!(matches!(registry.build("count", &[]), Err(SyntaxError::UnknownCommand(..))));
+    }
+}
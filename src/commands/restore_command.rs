@@ -0,0 +1,34 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument};
+
+#[derive(Debug)]
+pub struct RestoreCmd {
+    name: String,
+}
+
+impl super::Command for RestoreCmd {
+    /// Build a RestoreCmd.
+    /// Takes in an array of arguments. The only argument is the name of the snapshot.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        // validate argument count
+        if arguments.len() != 1 {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        Ok(Self {
+            name: super::name_from_argument(&arguments[0])?,
+        })
+    }
+
+    /// Execute the restore command. This replaces the whole tree with a saved snapshot.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let snapshot = ctx.snapshot(&self.name)
+            .ok_or(CommandError::NotFound(format!["snapshot {}", self.name]))?;
+
+        snapshot.restore(&ctx);
+        Ok(CommandOutput::new())
+    }
+}
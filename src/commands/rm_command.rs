@@ -1,53 +1,211 @@
 use crate::Context;
+use super::{CommandError, CommandOutput};
 use std::fmt::Debug;
 use std::rc::Rc;
 use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
+use crate::tree::Node;
 
 #[derive(Debug)]
 pub struct RmCmd {
-    path: NodePath,
-    name: String,
+    paths: Vec<NodePath>,
+
+    /// Remove folders and everything inside them.
+    recursive: bool,
+
+    /// Ignore missing files and never prompt.
+    force: bool,
+
+    /// Ask before removing each file and folder.
+    interactive: bool,
 }
 
 impl super::Command for RmCmd {
     /// Build a new RmCmd.
     /// Takes in an array of arguments.
     /// The build function fails if the conditions for the arguments are invalid such
-    /// as invalid type or the wrong number of arguments supplied.
+    /// as invalid type or no paths being supplied.
+    /// `-r` removes folders, `-f` ignores missing files and `-i` prompts before each removal.
     fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        let mut recursive = false;
+        let mut force = false;
+        let mut interactive = false;
+        let mut paths = Vec::new();
+
+        for argument in arguments.iter() {
+            match argument {
+                Argument::Flag(flag) => {
+                    for letter in super::flag_letters(flag)? {
+                        match letter {
+                            'r' | 'R' => recursive = true,
+                            'f' => force = true,
+                            'i' => interactive = true,
+                            _ => return Err(SyntaxError::InvalidFlag),
+                        }
+                    }
+                },
+                Argument::Path(path) => paths.push(path.clone()),
+                _ => return Err(SyntaxError::InvalidType),
+            }
+        }
+
         // check the argument count is correct
-        if arguments.len() != 1 {
+        if paths.is_empty() {
             return Err(SyntaxError::InvalidArguments);
         }
 
-        // check that the type is a path
-        let path = match &arguments[0] {
-            Argument::Path(path) => path,
-            _ => return Err(SyntaxError::InvalidType),
-        };
-
-        // check that the path type resolves to a file
-        let name = match path.last().unwrap() {
-            NodePathSegment::File(name) => name.to_string(),
-            _ => return Err(SyntaxError::InvalidType),
-        };
+        // check that each path names a file or folder
+        if paths.iter().any(|path| matches!(path.last(), Some(NodePathSegment::Root) | None)) {
+            return Err(SyntaxError::InvalidType);
+        }
 
         Ok(Self {
-            path: path[..path.len() - 1].to_vec(),
-            name,
+            paths,
+            recursive,
+            force,
+            interactive: interactive && !force,
         })
     }
 
-    /// Execute the rm command and remove a file based on self.path
-    fn execute(&self, ctx: Rc<Context>) {
-        if let Ok(target) = ctx.node_from_path(&self.path) {
-            if let Err(e) = target.remove(&self.name) {
-                // no file with supplied name is found in the parent folder
-                println!("{}", e);
+    /// Execute the rm command and remove each file, or folder with `-r`, in self.paths.
+    /// Every path is checked before anything is removed, so nothing is removed if one fails.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let mut targets: Vec<Rc<Node>> = Vec::new();
+        for path in self.paths.iter() {
+            // missing files and globs that match nothing are not an error with -f.
+            let found = super::expand(&ctx, path).and_then(|paths| {
+                paths.iter()
+                    .map(|path| ctx.find_node(path).map_err(CommandError::from))
+                    .collect::<Result<Vec<_>, _>>()
+            });
+            let found = match found {
+                Ok(found) => found,
+                Err(_) if self.force => continue,
+                Err(e) => return Err(e),
+            };
+
+            for target in found {
+                self.check_target(&ctx, &target)?;
+                targets.push(target);
+            }
+        }
+
+        for target in targets.iter() {
+            // a node given twice, or inside a folder that was removed, is already gone.
+            if !ctx.root().is_ancestor_of(target) {
+                continue;
             }
-        } else {
-            // parent path is not found in the file tree
-            println!("Invalid path");
+
+            if self.interactive {
+                Self::remove_interactively(&ctx, target);
+            } else {
+                let parent = target.parent_node().unwrap();
+                ctx.remove_node(parent, &target.name().unwrap()).map_err(CommandError::NotFound)?;
+            }
+        }
+
+        Ok(CommandOutput::new())
+    }
+}
+
+impl RmCmd {
+    /// Check that `target` can be removed.
+    fn check_target(&self, ctx: &Context, target: &Rc<Node>) -> Result<(), CommandError> {
+        if let Node::Root { .. } = **target {
+            return Err(CommandError::PermissionDenied("cannot remove the root".to_string()));
         }
+
+        if target.children().is_some() && !self.recursive {
+            return Err(CommandError::IsADirectory(target.to_string()));
+        }
+
+        // the current directory cannot be removed from under the user.
+        if target.is_ancestor_of(&ctx.current_dir()) {
+            return Err(CommandError::PermissionDenied(format!["{} contains the current directory", target]));
+        }
+
+        Ok(())
+    }
+
+    /// Remove `node` and everything inside it, asking before each removal.
+    /// A folder is only removed if everything inside it was removed.
+    fn remove_interactively(ctx: &Context, node: &Rc<Node>) {
+        if let Some(children) = node.children() {
+            if !ctx.confirm(&format!["Descend into folder {}?", node]) {
+                return;
+            }
+
+            let children = children.borrow().clone();
+            for child in children.iter() {
+                Self::remove_interactively(ctx, child);
+            }
+
+            if !node.children().unwrap().borrow().is_empty() {
+                return;
+            }
+        }
+
+        let kind = if node.children().is_some() { "folder" } else { "file" };
+        if ctx.confirm(&format!["Remove {} {}?", kind, node]) {
+            let parent = node.parent_node().unwrap();
+            ctx.remove_node(parent, &node.name().unwrap()).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn folders_need_recursive() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let user = ctx.current_dir();
+
+        assert_eq!(run(&ctx, "rm photos"), Err(CommandError::IsADirectory("/home/test_user/photos".to_string())));
+        assert!(user.child("photos").is_some());
+
+        run(&ctx, "rm -r music").unwrap();
+        assert!(user.child("music").is_none());
+        assert_eq!(user.size(), Some(2));
+        assert!(ctx.verify_sizes().is_ok());
+    }
+
+    #[test]
+    fn force_ignores_missing() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let user = ctx.current_dir();
+
+        assert_eq!(run(&ctx, "rm missing.txt"), Err(CommandError::NotFound("missing.txt".to_string())));
+        run(&ctx, "rm -f missing.txt").unwrap();
+        run(&ctx, "rm -f documents/cv.pdf").unwrap();
+        assert!(user.child("documents").unwrap().child("cv.pdf").is_none());
+    }
+
+    #[test]
+    fn keeps_current_dir() {
+        let ctx = Rc::new(build_tree("test_user"));
+        run(&ctx, "cd photos/japan2026").unwrap();
+
+        assert!(matches!(run(&ctx, "rm -r ../japan2026"), Err(CommandError::PermissionDenied(..))));
+        assert!(matches!(run(&ctx, "rm -r /home/test_user/photos"), Err(CommandError::PermissionDenied(..))));
+        assert!(matches!(run(&ctx, "rm -rf /home"), Err(CommandError::PermissionDenied(..))));
+        assert!(ctx.root().is_ancestor_of(&ctx.current_dir()));
+    }
+
+    #[test]
+    fn interactive_asks_the_prompt() {
+        let ctx = Rc::new(build_tree("test_user"));
+        let documents = ctx.current_dir().child("documents").unwrap();
+
+        // without a prompt nothing is removed.
+        run(&ctx, "rm -i documents/cv.pdf").unwrap();
+        assert!(documents.child("cv.pdf").is_some());
+
+        ctx.set_prompt(|question| !question.contains("data.dat"));
+        run(&ctx, "rm -ri documents").unwrap();
+        assert!(documents.child("cv.pdf").is_none());
+        assert!(documents.child("data.dat").is_some());
     }
 }
@@ -1,12 +1,12 @@
 use crate::Context;
+use super::{CommandError, CommandOutput};
 use std::fmt::Debug;
 use std::rc::Rc;
-use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
+use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment, path_to_string};
 
 #[derive(Debug)]
 pub struct RmdirCmd {
     path: NodePath,
-    name: String,
 }
 
 impl super::Command for RmdirCmd {
@@ -25,34 +25,57 @@ impl super::Command for RmdirCmd {
             _ => return Err(SyntaxError::InvalidType),
         };
 
-        // get the name of the folder
-        let name = match path.last().unwrap() {
-            // make sure that the path resolves to a dir
-            NodePathSegment::Dir(name) => name.to_string(),
-            _ => return Err(SyntaxError::InvalidType),
-        };
+        // make sure that the path names a folder
+        if let Some(NodePathSegment::Root | NodePathSegment::Parent) | None = path.last() {
+            return Err(SyntaxError::InvalidType);
+        }
 
-        Ok(Self {
-            path: path[..path.len() - 1].to_vec(),
-            name,
-        })
+        Ok(Self { path: path.clone() })
     }
 
-    /// Execute the Rmdir command.
-    fn execute(&self, ctx: Rc<Context>) {
-        if let Ok(target) = ctx.node_from_path(&self.path) {
-            // check that the depth of the target is not less than the current directory.
-            if target.depth() < ctx.current_dir().borrow().depth() {
-                println!("Cannot remove directory as it is less deep than the current directory");
-                return;
-            }
-
-            // remove the target
-            if let Err(e) = target.remove(&self.name) {
-                println!("{}", e);
-            }
-        } else {
-            println!("Invalid path");
+    /// Execute the Rmdir command. Only empty folders can be removed.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let path = super::single(super::expand(&ctx, &self.path)?, &self.path)?;
+        let Some((NodePathSegment::Name(name), parent)) = path.split_last() else {
+            return Err(CommandError::NotFound(path_to_string(&path)));
+        };
+
+        let target = ctx.node_from_path(&parent.to_vec())?;
+        let folder = target.child(name).ok_or(CommandError::NotFound(name.clone()))?;
+
+        if folder.children().is_none() {
+            return Err(CommandError::NotADirectory(folder.to_string()));
+        }
+
+        // check that the folder is not the current directory or one of its parents.
+        if folder.is_ancestor_of(&ctx.current_dir()) {
+            return Err(CommandError::PermissionDenied(format!["{} contains the current directory", folder]));
         }
+
+        if !folder.children().unwrap().borrow().is_empty() {
+            return Err(CommandError::DirectoryNotEmpty(folder.to_string()));
+        }
+
+        // remove the target
+        ctx.remove_node(target, name).map_err(CommandError::NotFound)?;
+        Ok(CommandOutput::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn only_empty_folders() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        assert_eq!(run(&ctx, "rmdir photos"), Err(CommandError::DirectoryNotEmpty("/home/test_user/photos".to_string())));
+        assert!(matches!(run(&ctx, "rmdir documents/cv.pdf"), Err(CommandError::NotADirectory(..))));
+
+        run(&ctx, "rmdir photos/japan2026 && rmdir photos").unwrap();
+        assert!(ctx.current_dir().child("photos").is_none());
     }
 }
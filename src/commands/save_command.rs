@@ -0,0 +1,40 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument, path_to_string};
+use crate::persist;
+
+#[derive(Debug)]
+pub struct SaveCmd {
+    file: PathBuf,
+}
+
+impl super::Command for SaveCmd {
+    /// Build a SaveCmd.
+    /// Takes in an array of arguments. The only argument is the file on the host
+    /// filesystem to save the tree into.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        // validate argument count
+        if arguments.len() != 1 {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        // validate that the argument is a path
+        match &arguments[0] {
+            Argument::Path(path) => Ok(Self {
+                file: PathBuf::from(path_to_string(path)),
+            }),
+            _ => Err(SyntaxError::InvalidType),
+        }
+    }
+
+    /// Execute the save command. This writes the whole tree to a JSON file.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        persist::save_to_file(&ctx, &self.file)
+            .map_err(|e| CommandError::Io(format!["Could not save the tree: {}", e]))?;
+
+        Ok(CommandOutput::new())
+    }
+}
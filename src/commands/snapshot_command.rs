@@ -0,0 +1,34 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument};
+use crate::snapshot::Snapshot;
+
+#[derive(Debug)]
+pub struct SnapshotCmd {
+    name: String,
+}
+
+impl super::Command for SnapshotCmd {
+    /// Build a SnapshotCmd.
+    /// Takes in an array of arguments. The only argument is the name of the snapshot.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        // validate argument count
+        if arguments.len() != 1 {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        Ok(Self {
+            name: super::name_from_argument(&arguments[0])?,
+        })
+    }
+
+    /// Execute the snapshot command. This saves a copy of the whole tree under a name,
+    /// replacing any snapshot with the same name.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let snapshot = Snapshot::capture(&ctx);
+        ctx.add_snapshot(&self.name, snapshot);
+        Ok(CommandOutput::new())
+    }
+}
@@ -0,0 +1,33 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument};
+
+#[derive(Debug)]
+pub struct SnapshotsCmd;
+
+impl super::Command for SnapshotsCmd {
+    /// Build a SnapshotsCmd. This command takes no arguments.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        if !arguments.is_empty() {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        Ok(Self)
+    }
+
+    /// Execute the snapshots command. This lists the names of the saved snapshots.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let mut output = CommandOutput::new();
+
+        for (name, snapshot) in ctx.snapshots() {
+            let size: usize = snapshot.root().children().unwrap().borrow().iter()
+                .map(|child| child.size().unwrap())
+                .sum();
+            output.push(format!["{} {}KB", name, size]);
+        }
+
+        Ok(output)
+    }
+}
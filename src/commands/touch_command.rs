@@ -1,84 +1,115 @@
 use crate::Context;
+use super::{CommandError, CommandOutput};
 use std::fmt::Debug;
 use std::rc::Rc;
-use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
+use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment, path_to_string};
 use crate::tree::Node;
 
 #[derive(Debug)]
 pub struct TouchCmd {
-    path: NodePath,
-    file_name: String,
-    size: usize,
+    /// The path of each file.
+    paths: Vec<NodePath>,
+
+    /// Size of the files in kilobytes, if one was supplied.
+    size: Option<usize>,
 }
 
 impl super::Command for TouchCmd {
     /// Build a new TouchCmd.
     /// Takes in an array of arguments. This function also validates the
     /// arguments and returns a SyntaxError if they are invalid.
+    /// Any number of files can be given, followed by an optional size for all of them.
     fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        // the size can only be the last argument
+        let (size, arguments) = match arguments.split_last() {
+            Some((Argument::Number(n), rest)) => (Some(*n), rest),
+            _ => (None, arguments),
+        };
+
         // check that the supplied argument count is correct.
-        if arguments.len() != 2 && arguments.len() != 1 {
+        if arguments.is_empty() {
             return Err(SyntaxError::InvalidArguments);
         }
 
-        // check that the first argument is a path
-        let path = match &arguments[0] {
-            Argument::Path(path) => path,
-            _ => return Err(SyntaxError::InvalidType),
-        };
-
-        // get the file name
-        let file_name = match path.last().unwrap() {
-            NodePathSegment::File(name) => name.clone(),
-            _ => return Err(SyntaxError::InvalidType),
-        };
-
-        // check that the second argument is a size, set size to 1 if not supplied
-        let size = match arguments.get(1) {
-            Some(Argument::Number(n)) => *n,
-            Some(_) => return Err(SyntaxError::InvalidType),
-            None => 1,
-        };
+        // check that every other argument is the path of a file
+        let mut paths = Vec::new();
+        for argument in arguments.iter() {
+            match argument {
+                Argument::Path(path) if matches!(path.last(), Some(NodePathSegment::Name(..) | NodePathSegment::Pattern(..))) => {
+                    paths.push(path.clone());
+                },
+                _ => return Err(SyntaxError::InvalidType),
+            }
+        }
 
-        Ok(Self {
-            path: path[..path.len() - 1].to_vec(),
-            file_name,
-            size,
-        })
+        Ok(Self { paths, size })
     }
 
-    /// Execute the touch command, this creates a new file.
-    fn execute(&self, ctx: Rc<Context>) {
-        // validate various things about file name.
-        if self.file_name.contains(" ") {
-            println!("The file name cannot contain spaces");
-            return;
+    /// Execute the touch command, this creates a new file for each path.
+    /// If a file already exists its modified time is updated, and it is resized if a
+    /// size was supplied. The files are changed in order, stopping at the first that fails.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let policy = ctx.policy();
+        if let Some(size) = self.size {
+            policy.limits.check_file_size(size).map_err(CommandError::InvalidSize)?;
         }
 
-        if self.file_name.len() > 12 {
-            println!("The file name cannot be over 12 characters");
-            return;
-        }
+        for path in self.paths.iter() {
+            for path in super::expand_new(&ctx, path)? {
+                let Some((NodePathSegment::Name(file_name), folder)) = path.split_last() else {
+                    return Err(CommandError::NotFound(path_to_string(&path)));
+                };
 
-        if self.size >= 4194304 {
-            println!("The file size can only be up to 4GB");
-            return;
+                let target = ctx.node_from_path(&folder.to_vec())?;
+                self.touch_file(&ctx, target, file_name)?;
+            }
         }
 
-        if self.size == 0 {
-            println!("Cannot create a file with 0 size");
-            return;
-        }
-        
-        if self.file_name.split(".").last().unwrap().len() != 3 {
-            println!("File extension must be 3 characters because Doc said so.");
-            return;
+        Ok(CommandOutput::new())
+    }
+}
+
+impl TouchCmd {
+    /// Create the file `file_name` in `target`, or update it if it already exists.
+    fn touch_file(&self, ctx: &Context, target: Rc<Node>, file_name: &str) -> Result<(), CommandError> {
+        if let Some(existing) = target.child(file_name) {
+            if existing.children().is_some() {
+                return Err(CommandError::IsADirectory(existing.to_string()));
+            }
+
+            if let Some(size) = self.size.filter(|size| Some(*size) != existing.size()) {
+                ctx.resize_node(Rc::clone(&existing), size)?;
+            }
+            existing.touch();
+            return Ok(());
         }
 
-        // create the new file in target.
-        if let Ok(target) = ctx.node_from_path(&self.path) {
-            let new_file = Rc::new(Node::new_file(&self.file_name, self.size));
-            target.add(new_file).unwrap();
+        if let Node::Root { .. } = *target {
+            return Err(CommandError::PermissionDenied("cannot create a file in the root".to_string()));
         }
+
+        ctx.policy().naming.check_file_name(file_name).map_err(CommandError::InvalidName)?;
+
+        // create the new file in target, with a size of 1 if one was not supplied.
+        let new_file = Rc::new(Node::new_file(file_name, self.size.unwrap_or(1)));
+        ctx.add_node(target, new_file)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn no_files_in_root() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        assert!(matches!(run(&ctx, "touch /a.txt"), Err(CommandError::PermissionDenied(..))));
+        assert_eq!(ctx.root().children().unwrap().borrow().len(), 1);
+
+        run(&ctx, "touch /home/a.txt").unwrap();
     }
 }
@@ -0,0 +1,187 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use super::format::format_size;
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument, NodePath, NodePathSegment};
+use crate::tree::Node;
+
+#[derive(Debug)]
+pub struct TreeCmd {
+    path: NodePath,
+
+    /// The deepest level below the folder to show, where its entries have a level of 1.
+    max_depth: Option<usize>,
+
+    /// Whether to only show folders.
+    dirs_only: bool,
+
+    /// Whether to show entries whose names start with a dot.
+    all: bool,
+
+    human: bool,
+}
+
+#[derive(Debug, Default)]
+/// The number of entries shown, for the summary line.
+struct Counts {
+    folders: usize,
+    files: usize,
+}
+
+impl super::Command for TreeCmd {
+    /// Build a TreeCmd.
+    /// Takes in an array of arguments. The only path is the folder to show, which defaults
+    /// to the current directory. `-L N` limits the depth, `-d` only shows folders, `-a` shows
+    /// hidden entries and `-h` shows sizes in larger units.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        let mut command = Self {
+            path: Vec::new(),
+            max_depth: None,
+            dirs_only: false,
+            all: false,
+            human: false,
+        };
+        let mut paths = Vec::new();
+
+        let mut arguments = arguments.iter();
+        while let Some(argument) = arguments.next() {
+            match argument {
+                Argument::Flag(flag) if flag == "-L" => {
+                    // the depth must be followed by a level of at least 1
+                    match arguments.next() {
+                        Some(Argument::Number(n)) if *n > 0 => command.max_depth = Some(*n),
+                        _ => return Err(SyntaxError::InvalidArguments),
+                    }
+                },
+                Argument::Flag(flag) => {
+                    for letter in super::flag_letters(flag)? {
+                        match letter {
+                            'd' => command.dirs_only = true,
+                            'a' => command.all = true,
+                            'h' => command.human = true,
+                            _ => return Err(SyntaxError::InvalidFlag),
+                        }
+                    }
+                },
+                Argument::Path(path) => paths.push(path),
+                _ => return Err(SyntaxError::InvalidType),
+            }
+        }
+
+        match paths.as_slice() {
+            [] => {},
+            [path] => {
+                // make sure that the path is not the tree root
+                if let Some(NodePathSegment::Root) | None = path.last() {
+                    return Err(SyntaxError::InvalidType);
+                }
+                command.path = path.to_vec();
+            },
+            _ => return Err(SyntaxError::InvalidArguments),
+        }
+
+        Ok(command)
+    }
+
+    /// Execute the tree command. This shows every folder and file below a folder, followed
+    /// by the number of folders and files shown.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        let path = super::single(super::expand(&ctx, &self.path)?, &self.path)?;
+        let target = ctx.find_node(&path)?;
+        if target.children().is_none() {
+            return Err(CommandError::NotADirectory(target.to_string()));
+        }
+
+        let mut output = CommandOutput::new();
+        output.push(format!["{} {}", target, format_size(target.size().unwrap(), self.human)]);
+
+        let mut counts = Counts::default();
+        self.walk(&target, target.depth(), "", &mut output, &mut counts);
+
+        output.push("");
+        output.push(format![
+            "{} {}, {} {}",
+            counts.folders,
+            if counts.folders == 1 { "directory" } else { "directories" },
+            counts.files,
+            if counts.files == 1 { "file" } else { "files" },
+        ]);
+        Ok(output)
+    }
+}
+
+impl TreeCmd {
+    /// Show the entries of `folder`, each preceded by `prefix` and a connector.
+    /// `start_depth` is the depth of the folder the command was run on.
+    fn walk(&self, folder: &Rc<Node>, start_depth: usize, prefix: &str, output: &mut CommandOutput, counts: &mut Counts) {
+        let mut children: Vec<Rc<Node>> = folder.child_nodes().into_iter()
+            .filter(|child| self.all || !child.name().unwrap().starts_with('.'))
+            .filter(|child| !self.dirs_only || child.children().is_some())
+            .collect();
+        children.sort_by_key(|child| child.name());
+
+        for (i, child) in children.iter().enumerate() {
+            let last = i == children.len() - 1;
+            let connector = if last { "└── " } else { "├── " };
+            let size = format_size(child.size().unwrap(), self.human);
+
+            if child.children().is_none() {
+                output.push(format!["{}{}{} {}", prefix, connector, child.name().unwrap(), size]);
+                counts.files += 1;
+                continue;
+            }
+
+            output.push(format!["{}{}{}/ {}", prefix, connector, child.name().unwrap(), size]);
+            counts.folders += 1;
+
+            if self.max_depth.is_none_or(|max_depth| child.depth() - start_depth < max_depth) {
+                let child_prefix = format!["{}{}", prefix, if last { "    " } else { "│   " }];
+                self.walk(child, start_depth, &child_prefix, output, counts);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::tests::run;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn draw_subtree() {
+        let ctx = Rc::new(build_tree("test_user"));
+        ctx.add_node(ctx.current_dir().child("photos").unwrap(), Rc::new(Node::new_file("a.png", 3))).unwrap();
+
+        assert_eq!(run(&ctx, "tree photos").unwrap(), [
+            "/home/test_user/photos 3KB",
+            "├── a.png 3KB",
+            "└── japan2026/ 0KB",
+            "",
+            "1 directory, 1 file",
+        ]);
+
+        let lines = run(&ctx, "tree").unwrap();
+        assert_eq!(lines[1..4], ["├── documents/ 2KB", "│   ├── cv.pdf 1KB", "│   └── data.dat 1KB"]);
+        assert_eq!(lines.last().unwrap(), "5 directories, 13 files");
+    }
+
+    #[test]
+    fn depth_and_folders_only() {
+        let ctx = Rc::new(build_tree("test_user"));
+
+        assert_eq!(run(&ctx, "tree -L 1").unwrap().last().unwrap(), "4 directories, 0 files");
+        assert_eq!(run(&ctx, "tree -d").unwrap(), [
+            "/home/test_user 12KB",
+            "├── documents/ 2KB",
+            "├── downloads/ 0KB",
+            "├── music/ 10KB",
+            "└── photos/ 0KB",
+            "    └── japan2026/ 0KB",
+            "",
+            "5 directories, 0 files",
+        ]);
+        assert_eq!(run(&ctx, "tree music/1.mp3"), Err(CommandError::NotADirectory("/home/test_user/music/1.mp3".to_string())));
+    }
+}
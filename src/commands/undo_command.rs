@@ -0,0 +1,28 @@
+use crate::Context;
+use super::{CommandError, CommandOutput};
+use std::fmt::Debug;
+use std::rc::Rc;
+use crate::parser::{SyntaxError, Argument};
+
+#[derive(Debug)]
+pub struct UndoCmd;
+
+impl super::Command for UndoCmd {
+    /// Build an UndoCmd. This command takes no arguments.
+    fn build(arguments: &[Argument]) -> Result<Self, SyntaxError> {
+        if !arguments.is_empty() {
+            return Err(SyntaxError::InvalidArguments);
+        }
+
+        Ok(Self)
+    }
+
+    /// Execute the undo command. This reverts the most recent change to the tree.
+    fn execute(&self, ctx: Rc<Context>) -> Result<CommandOutput, CommandError> {
+        if !ctx.undo() {
+            return Err(CommandError::InvalidOperation("Nothing to undo".to_string()));
+        }
+
+        Ok(CommandOutput::new())
+    }
+}
@@ -0,0 +1,250 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+use crate::Context;
+use crate::parser::NodePath;
+use crate::tree::Node;
+
+#[derive(Debug, Clone, Default)]
+/// Options controlling an export.
+pub struct ExportOptions {
+    /// Only plan the export without writing anything to the host filesystem.
+    pub dry_run: bool,
+}
+
+#[derive(Debug, PartialEq)]
+/// An entry created (or that would be created) on the host filesystem.
+pub enum ExportEntry {
+    Folder(PathBuf),
+
+    /// A file and its size in bytes.
+    File(PathBuf, u64),
+}
+
+impl fmt::Display for ExportEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportEntry::Folder(path) => write!(f, "{}/", path.display()),
+            ExportEntry::File(path, size) => write!(f, "{} ({} bytes)", path.display(), size),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// An error produced while exporting to the host filesystem.
+pub enum ExportError {
+    Io(PathBuf, io::Error),
+    InvalidSource,
+
+    /// A node name that would write outside of the target root, such as `..`.
+    OutsideTarget(String),
+
+    /// Paths that already exist on the host and would be overwritten.
+    Conflicts(Vec<PathBuf>),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            ExportError::InvalidSource => write!(f, "The source path is not in the tree"),
+            ExportError::OutsideTarget(name) => {
+                write!(f, "The name {} would write outside of the target folder", name)
+            },
+            ExportError::Conflicts(paths) => {
+                write!(f, "These paths already exist:")?;
+                for path in paths {
+                    write!(f, "\n  {}", path.display())?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// Join a node name onto `parent`, making sure the name is a single plain path component.
+fn join_name(parent: &Path, name: &str) -> Result<PathBuf, ExportError> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(component)), None) if component == name => Ok(parent.join(name)),
+        _ => Err(ExportError::OutsideTarget(name.to_string())),
+    }
+}
+
+/// Plan the entries needed to export `node` into the host folder `parent`.
+/// Existing paths that would be overwritten are added to `conflicts`.
+fn plan(
+    node: &Node,
+    parent: &Path,
+    entries: &mut Vec<ExportEntry>,
+    conflicts: &mut Vec<PathBuf>,
+) -> Result<(), ExportError> {
+    let path = join_name(parent, &node.name().unwrap())?;
+
+    // symbolic links are never followed so that an export cannot escape the target root.
+    let existing = match fs::symlink_metadata(&path) {
+        Ok(metadata) => Some(metadata),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(ExportError::Io(path, e)),
+    };
+
+    match node {
+        Node::Folder { children, .. } => {
+            match existing {
+                // existing folders are reused.
+                Some(metadata) if metadata.is_dir() => (),
+                Some(_) => conflicts.push(path.clone()),
+                None => entries.push(ExportEntry::Folder(path.clone())),
+            }
+
+            for child in children.borrow().iter() {
+                plan(child, &path, entries, conflicts)?;
+            }
+        },
+        Node::File { size, .. } => {
+            if existing.is_some() {
+                conflicts.push(path);
+            } else {
+                entries.push(ExportEntry::File(path, *size.borrow() as u64 * 1024));
+            }
+        },
+        Node::Root { .. } => return Err(ExportError::InvalidSource),
+    }
+
+    Ok(())
+}
+
+/// Create a planned entry on the host filesystem.
+/// Files are created with `set_len` so they are sparse on filesystems that support it.
+fn create(entry: &ExportEntry) -> Result<(), ExportError> {
+    match entry {
+        ExportEntry::Folder(path) => {
+            fs::create_dir(path).map_err(|e| ExportError::Io(path.clone(), e))
+        },
+        ExportEntry::File(path, size) => {
+            let file = fs::File::create_new(path).map_err(|e| ExportError::Io(path.clone(), e))?;
+            file.set_len(*size).map_err(|e| ExportError::Io(path.clone(), e))
+        },
+    }
+}
+
+/// Export the file or folder at `source` in the tree into the host folder `target_root`.
+/// Folders become directories and files become zero-filled files of their recorded size.
+/// Nothing is written if any path already exists as a file, or if `options.dry_run` is set.
+/// Returns the entries that were created (or would be created).
+pub fn export_tree(
+    ctx: &Context,
+    source: &NodePath,
+    target_root: &Path,
+    options: &ExportOptions,
+) -> Result<Vec<ExportEntry>, ExportError> {
+    let source: Rc<Node> = ctx.find_node(source).map_err(|_| ExportError::InvalidSource)?;
+
+    // the root has no name to export it under.
+    if let Node::Root { .. } = *source {
+        return Err(ExportError::InvalidSource);
+    }
+
+    let mut entries = Vec::new();
+    let mut conflicts = Vec::new();
+    plan(&source, target_root, &mut entries, &mut conflicts)?;
+
+    if !conflicts.is_empty() {
+        return Err(ExportError::Conflicts(conflicts));
+    }
+
+    if !options.dry_run {
+        fs::create_dir_all(target_root).map_err(|e| ExportError::Io(target_root.to_path_buf(), e))?;
+        for entry in entries.iter() {
+            create(entry)?;
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::NodePathSegment;
+    use crate::import::tests::HostPath;
+    use crate::tree::build_tree;
+
+    /// Get a folder on the host that does not exist yet for a test to export into.
+    fn host_folder(test_name: &str) -> HostPath {
+        HostPath::new(&format!["export-{}", test_name], "out")
+    }
+
+    #[test]
+    fn export_folder() {
+        let host = host_folder("folder");
+        let ctx = build_tree("test_user");
+        let source = vec![NodePathSegment::Name("documents".to_string())];
+
+        let entries = export_tree(&ctx, &source, &host, &ExportOptions::default()).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(fs::metadata(host.join("documents/cv.pdf")).unwrap().len(), 1024);
+
+        // exporting again conflicts with the files that now exist.
+        let result = export_tree(&ctx, &source, &host, &ExportOptions::default());
+        match result {
+            Err(ExportError::Conflicts(paths)) => assert_eq!(paths.len(), 2),
+            _ => panic!("expected conflicts"),
+        }
+    }
+
+    #[test]
+    fn export_dry_run() {
+        let host = host_folder("dry-run");
+        let ctx = build_tree("test_user");
+        let source = vec![NodePathSegment::Name("music".to_string())];
+
+        let options = ExportOptions { dry_run: true };
+        let entries = export_tree(&ctx, &source, &host, &options).unwrap();
+        assert_eq!(entries[0], ExportEntry::Folder(host.join("music")));
+        assert_eq!(entries.len(), 11);
+        assert!(!host.exists());
+    }
+
+    #[test]
+    fn export_refuses_to_escape() {
+        let host = host_folder("escape");
+        let ctx = build_tree("test_user");
+        let downloads = ctx.node_from_path(&vec![NodePathSegment::Name("downloads".to_string())]).unwrap();
+        downloads.add(Rc::new(Node::new_file("..", 1))).unwrap();
+
+        let source = vec![NodePathSegment::Name("downloads".to_string())];
+        let result = export_tree(&ctx, &source, &host, &ExportOptions::default());
+        assert!(matches!(result, Err(ExportError::OutsideTarget(..))));
+        assert!(!host.exists());
+    }
+
+    #[test]
+    fn export_file() {
+        let host = host_folder("file");
+        let ctx = build_tree("test_user");
+        let source = vec![NodePathSegment::Name("documents".to_string()), NodePathSegment::Name("cv.pdf".to_string())];
+
+        let entries = export_tree(&ctx, &source, &host, &ExportOptions::default()).unwrap();
+        assert_eq!(entries, [ExportEntry::File(host.join("cv.pdf"), 1024)]);
+        assert_eq!(fs::metadata(host.join("cv.pdf")).unwrap().len(), 1024);
+
+        let source = vec![NodePathSegment::Name("documents".to_string()), NodePathSegment::Name("missing.pdf".to_string())];
+        let result = export_tree(&ctx, &source, &host, &ExportOptions::default());
+        assert!(matches!(result, Err(ExportError::InvalidSource)));
+    }
+
+    #[test]
+    fn export_refuses_the_root() {
+        let host = host_folder("root");
+        let ctx = build_tree("test_user");
+
+        for source in [vec![NodePathSegment::Root], vec![NodePathSegment::Parent, NodePathSegment::Parent]] {
+            let result = export_tree(&ctx, &source, &host, &ExportOptions { dry_run: true });
+            assert!(matches!(result, Err(ExportError::InvalidSource)));
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::Context;
+use crate::parser::{NodePath, NodePathSegment};
+
+/// Check if `name` matches the shell pattern `pattern`.
+/// `*` matches any run of characters, `?` matches one character, and `[abc]` or `[a-z]`
+/// matches one character from a set, with `[!abc]` matching any character not in it.
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+
+    // where the last `*` was in the pattern, and where in the name it started matching.
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            star = Some((p, n));
+            p += 1;
+            continue;
+        }
+
+        if let Some(length) = match_one(&pattern[p..], name[n]) {
+            p += length;
+            n += 1;
+            continue;
+        }
+
+        // let the last `*` match one more character and try again from there.
+        match star {
+            Some((star_p, star_n)) => {
+                p = star_p + 1;
+                n = star_n + 1;
+                star = Some((star_p, star_n + 1));
+            },
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Check if `text` has any characters with a special meaning in a pattern.
+pub fn is_pattern(text: &str) -> bool {
+    text.contains(['*', '?', '['])
+}
+
+/// Escape the characters of `text` that have a special meaning in a pattern, so that the
+/// pattern only matches `text` itself.
+pub fn escape(text: &str) -> String {
+    let mut acc = String::new();
+    for c in text.chars() {
+        if is_pattern(c.encode_utf8(&mut [0; 4])) {
+            acc.push_str(&format!["[{}]", c]);
+        } else {
+            acc.push(c);
+        }
+    }
+
+    acc
+}
+
+/// Expand `path` into the path of every node in the tree that it matches, in order of name.
+/// Each `Pattern` segment is replaced with the name of a matching node, and only paths to
+/// nodes that exist are kept. Names starting with a dot are only matched by patterns that
+/// start with one. Returns no paths if nothing matches.
+pub fn expand_path(ctx: &Context, path: &NodePath) -> Vec<NodePath> {
+    let mut expanded: Vec<NodePath> = vec![Vec::new()];
+
+    for segment in path.iter() {
+        let NodePathSegment::Pattern(pattern) = segment else {
+            expanded.iter_mut().for_each(|prefix| prefix.push(segment.clone()));
+            continue;
+        };
+
+        expanded = expanded.into_iter()
+            .flat_map(|prefix| {
+                let mut children = match ctx.node_from_path(&prefix) {
+                    Ok(folder) => folder.child_nodes(),
+                    Err(_) => Vec::new(),
+                };
+                children.sort_by_key(|child| child.name());
+
+                children.into_iter()
+                    .filter_map(|child| child.name())
+                    .filter(|name| !name.starts_with('.') || pattern.starts_with('.'))
+                    .filter(|name| matches(pattern, name))
+                    .map(|name| {
+                        let mut path = prefix.clone();
+                        path.push(NodePathSegment::Name(name));
+                        path
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+
+    expanded.retain(|path| ctx.find_node(path).is_ok());
+    expanded
+}
+
+/// Match the first part of `pattern`, which is not a `*`, against `c`.
+/// Returns the number of pattern characters used if it matches.
+fn match_one(pattern: &[char], c: char) -> Option<usize> {
+    match pattern.first()? {
+        '?' => Some(1),
+        '[' => match class_end(pattern) {
+            Some(end) => class_matches(&pattern[1..end], c).then_some(end + 1),
+            // a `[` without a closing `]` is just a character.
+            None => (c == '[').then_some(1),
+        },
+        first => (*first == c).then_some(1),
+    }
+}
+
+/// Find the index of the `]` closing the set at the start of `pattern`.
+/// A `]` straight after the `[` or `[!` is part of the set.
+fn class_end(pattern: &[char]) -> Option<usize> {
+    let start = if pattern.get(1) == Some(&'!') { 2 } else { 1 };
+    pattern.iter().enumerate()
+        .skip(start + 1)
+        .find(|(_, c)| **c == ']')
+        .map(|(i, _)| i)
+}
+
+/// Check if `c` is in the set `class`, the characters between `[` and `]`.
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.first() {
+        Some('!') => (true, &class[1..]),
+        _ => (false, class),
+    };
+
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if class.get(i + 1) == Some(&'-') && i + 2 < class.len() {
+            found |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+
+    found != negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::path_to_string;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn wildcards() {
+        assert!(matches("*.mp3", "10.mp3"));
+        assert!(matches("*", ""));
+        assert!(matches("a*b*c", "aXbYbZc"));
+        assert!(!matches("*.mp3", "1.mp4"));
+        assert!(matches("?.mp3", "1.mp3"));
+        assert!(!matches("?.mp3", "10.mp3"));
+        assert!(matches("cv.pdf", "cv.pdf"));
+        assert!(!matches("cv", "cv.pdf"));
+    }
+
+    #[test]
+    fn sets() {
+        assert!(matches("[0-9].mp3", "7.mp3"));
+        assert!(!matches("[!0-9]*", "7.mp3"));
+        assert!(matches("[]a]", "]"));
+        assert!(matches("[ab", "[ab"));
+        assert!(matches("data.[dt]at", "data.dat"));
+        assert!(is_pattern("*.txt") && !is_pattern("a.txt"));
+    }
+
+    #[test]
+    fn escaped_text() {
+        assert_eq!(escape("a*b[1]?"), "a[*]b[[]1][?]");
+        assert!(matches(&escape("a*b[1]?"), "a*b[1]?"));
+        assert!(!matches(&escape("a*"), "ab"));
+    }
+
+    #[test]
+    fn expand_paths() {
+        let ctx = build_tree("test_user");
+        let names = |pattern: &[NodePathSegment]| -> Vec<String> {
+            expand_path(&ctx, &pattern.to_vec()).iter().map(path_to_string).collect()
+        };
+        let pattern = |text: &str| NodePathSegment::Pattern(text.to_string());
+        let name = |text: &str| NodePathSegment::Name(text.to_string());
+
+        assert_eq!(names(&[name("music"), pattern("1*")]), ["music/1.mp3", "music/10.mp3"]);
+        assert_eq!(names(&[name("music"), pattern("?.mp3")]).len(), 9);
+        assert_eq!(names(&[pattern("*"), pattern("c*")]), ["documents/cv.pdf"]);
+        assert_eq!(names(&[pattern("d*"), name("data.dat")]), ["documents/data.dat"]);
+        assert_eq!(names(&[NodePathSegment::Root, pattern("h?me")]), ["/home"]);
+        assert!(names(&[pattern("*.mp3")]).is_empty());
+        assert!(names(&[pattern("*.pdf"), pattern("*")]).is_empty());
+    }
+}
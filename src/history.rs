@@ -0,0 +1,314 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::tree::Node;
+
+/// The number of changes kept in the history unless configured otherwise.
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+#[derive(Debug, Clone)]
+/// A change made to the tree that can be reversed.
+/// The nodes are held directly so removed subtrees keep their children, sizes and links.
+pub enum Operation {
+    /// `node` was added to `parent` at position `index`.
+    Insert {
+        parent: Rc<Node>,
+        index: usize,
+        node: Rc<Node>,
+    },
+    /// `node` was removed from position `index` in `parent`.
+    Remove {
+        parent: Rc<Node>,
+        index: usize,
+        node: Rc<Node>,
+    },
+    /// The size of the file `node` was changed.
+    Resize {
+        node: Rc<Node>,
+        from: usize,
+        to: usize,
+    },
+    /// `node` was moved or renamed.
+    Move {
+        node: Rc<Node>,
+        from: Location,
+        to: Location,
+    },
+}
+
+#[derive(Debug, Clone)]
+/// Where a node is in the tree and what it is named.
+pub struct Location {
+    pub parent: Rc<Node>,
+    pub index: usize,
+    pub name: String,
+}
+
+impl Location {
+    /// Take the node at this location out of the tree.
+    fn leave(&self) {
+        Rc::clone(&self.parent).remove_at(self.index);
+    }
+
+    /// Name `node` and put it into this location.
+    fn enter(&self, node: &Rc<Node>) {
+        node.set_name(&self.name).unwrap();
+        Rc::clone(&self.parent).insert(self.index, Rc::clone(node)).unwrap();
+    }
+}
+
+impl Operation {
+    /// Get the folder that the operation changed.
+    pub fn parent(&self) -> Option<Rc<Node>> {
+        match self {
+            Operation::Insert { parent, .. } | Operation::Remove { parent, .. } => Some(Rc::clone(parent)),
+            Operation::Move { to, .. } => Some(Rc::clone(&to.parent)),
+            Operation::Resize { node, .. } => node.parent()?.borrow().upgrade(),
+        }
+    }
+
+    /// Reverse the operation.
+    fn revert(&self) {
+        match self {
+            Operation::Insert { parent, index, .. } => {
+                Rc::clone(parent).remove_at(*index);
+            },
+            Operation::Remove { parent, index, node } => {
+                Rc::clone(parent).insert(*index, Rc::clone(node)).unwrap();
+            },
+            Operation::Move { node, from, to } => {
+                to.leave();
+                from.enter(node);
+            },
+            Operation::Resize { node, from, .. } => {
+                Rc::clone(node).resize(*from).unwrap();
+            },
+        }
+    }
+
+    /// Perform the operation again after it has been reverted.
+    fn reapply(&self) {
+        match self {
+            Operation::Insert { parent, index, node } => {
+                Rc::clone(parent).insert(*index, Rc::clone(node)).unwrap();
+            },
+            Operation::Remove { parent, index, .. } => {
+                Rc::clone(parent).remove_at(*index);
+            },
+            Operation::Move { node, from, to } => {
+                from.leave();
+                to.enter(node);
+            },
+            Operation::Resize { node, to, .. } => {
+                Rc::clone(node).resize(*to).unwrap();
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A journal of changes made to the tree. Each change is the group of operations made by one
+/// command, so that undo and redo act on whole commands.
+/// Recording a new operation discards anything that could be redone.
+pub struct History {
+    undo: VecDeque<Vec<Operation>>,
+    redo: Vec<Vec<Operation>>,
+
+    /// The operations recorded since `begin` was called, if a change is being grouped.
+    group: Option<Vec<Operation>>,
+
+    /// The maximum number of changes that can be undone.
+    depth: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            group: None,
+            depth: DEFAULT_HISTORY_DEPTH,
+        }
+    }
+}
+
+impl History {
+    /// Start grouping operations. Every operation recorded until `commit` is one change.
+    pub fn begin(&mut self) {
+        self.group.get_or_insert_with(Vec::new);
+    }
+
+    /// Stop grouping operations and add the group to the history if anything was recorded.
+    pub fn commit(&mut self) {
+        if let Some(group) = self.group.take().filter(|group| !group.is_empty()) {
+            self.undo.push_back(group);
+            self.trim();
+        }
+    }
+
+    /// Record an operation that has just been performed. Outside of a group, the operation is
+    /// a change on its own.
+    pub fn record(&mut self, operation: Operation) {
+        self.redo.clear();
+        match &mut self.group {
+            Some(group) => group.push(operation),
+            None => {
+                self.undo.push_back(vec![operation]);
+                self.trim();
+            },
+        }
+    }
+
+    /// Revert the most recent change and return its operations.
+    pub fn undo(&mut self) -> Option<Vec<Operation>> {
+        let operations = self.undo.pop_back()?;
+        for operation in operations.iter().rev() {
+            operation.revert();
+        }
+        self.redo.push(operations.clone());
+        Some(operations)
+    }
+
+    /// Reapply the most recently undone change and return its operations.
+    pub fn redo(&mut self) -> Option<Vec<Operation>> {
+        let operations = self.redo.pop()?;
+        for operation in operations.iter() {
+            operation.reapply();
+        }
+        self.undo.push_back(operations.clone());
+        Some(operations)
+    }
+
+    /// Set the maximum number of changes that can be undone.
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.trim();
+    }
+
+    /// Forget every recorded operation, including those in the current group.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        if let Some(group) = &mut self.group {
+            group.clear();
+        }
+    }
+
+    /// Drop the oldest changes until the history fits in its depth.
+    fn trim(&mut self) {
+        while self.undo.len() > self.depth {
+            self.undo.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::NodePathSegment;
+    use crate::tree::{build_tree, Node};
+    use std::rc::Rc;
+
+    #[test]
+    fn undo_and_redo_add() {
+        let ctx = build_tree("test_user");
+        let downloads = ctx.node_from_path(&vec![NodePathSegment::Name("downloads".to_string())]).unwrap();
+
+        ctx.add_node(Rc::clone(&downloads), Rc::new(Node::new_file("a.txt", 3))).unwrap();
+        assert!(ctx.undo());
+        assert_eq!(downloads.children().unwrap().borrow().len(), 0);
+        assert!(!ctx.undo());
+
+        assert!(ctx.redo());
+        assert_eq!(downloads.size().unwrap(), 3);
+        assert!(!ctx.redo());
+    }
+
+    #[test]
+    fn undo_restores_removed_subtree() {
+        let ctx = build_tree("test_user");
+        let user = ctx.current_dir();
+
+        ctx.remove_node(Rc::clone(&user), "music").unwrap();
+        assert_eq!(user.size().unwrap(), 2);
+
+        assert!(ctx.undo());
+        let music = Rc::clone(&user.children().unwrap().borrow()[2]);
+        assert_eq!(music.name().unwrap(), "music");
+        assert_eq!(music.size().unwrap(), 10);
+        assert_eq!(music.children().unwrap().borrow()[0].to_string(), "/home/test_user/music/1.mp3");
+        assert!(ctx.verify_sizes().is_ok());
+    }
+
+    #[test]
+    fn undo_leaves_removed_current_dir() {
+        let ctx = build_tree("test_user");
+        let downloads = ctx.node_from_path(&vec![NodePathSegment::Name("downloads".to_string())]).unwrap();
+        let folder = Rc::new(Node::new_folder("new"));
+
+        ctx.add_node(Rc::clone(&downloads), Rc::clone(&folder)).unwrap();
+        ctx.set_current_dir(folder);
+        ctx.undo();
+
+        assert!(Rc::ptr_eq(&ctx.current_dir(), &downloads));
+    }
+
+    #[test]
+    fn undo_groups() {
+        let ctx = build_tree("test_user");
+        let user = ctx.current_dir();
+        let child = |name: &str| {
+            user.children().unwrap().borrow().iter()
+                .find(|child| child.name().as_deref() == Some(name))
+                .cloned()
+        };
+
+        ctx.begin_change();
+        ctx.remove_node(Rc::clone(&user), "music").unwrap();
+        ctx.add_node(Rc::clone(&user), Rc::new(Node::new_file("music", 4))).unwrap();
+        ctx.commit_change();
+        ctx.remove_node(Rc::clone(&user), "photos").unwrap();
+
+        assert!(ctx.undo() && ctx.undo());
+        assert_eq!(child("music").unwrap().size(), Some(10));
+        assert!(!ctx.undo());
+
+        assert!(ctx.redo());
+        assert_eq!(child("music").unwrap().size(), Some(4));
+        assert!(child("photos").is_some());
+    }
+
+    #[test]
+    fn history_depth() {
+        let ctx = build_tree("test_user");
+        ctx.set_history_depth(1);
+        let user = ctx.current_dir();
+
+        ctx.remove_node(Rc::clone(&user), "music").unwrap();
+        ctx.remove_node(Rc::clone(&user), "photos").unwrap();
+
+        assert!(ctx.undo());
+        assert!(!ctx.undo());
+    }
+
+    #[test]
+    fn undo_move() {
+        let ctx = build_tree("test_user");
+        let user = ctx.current_dir();
+        let music = user.child("music").unwrap();
+        let photos = user.child("photos").unwrap();
+
+        ctx.move_node(Rc::clone(&music), Rc::clone(&photos), "songs").unwrap();
+        assert_eq!(photos.size().unwrap(), 10);
+        assert_eq!(music.to_string(), "/home/test_user/photos/songs");
+
+        assert!(ctx.undo());
+        assert_eq!(photos.size().unwrap(), 0);
+        assert!(Rc::ptr_eq(&user.children().unwrap().borrow()[2], &music));
+        assert_eq!(music.to_string(), "/home/test_user/music");
+        assert_eq!(music.children().unwrap().borrow()[0].depth(), 4);
+
+        assert!(ctx.redo());
+        assert_eq!(music.to_string(), "/home/test_user/photos/songs");
+        assert!(ctx.verify_sizes().is_ok());
+    }
+}
@@ -0,0 +1,405 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::Context;
+use crate::parser::NodePath;
+use crate::policy::Policy;
+use crate::tree::{self, Node, NodeError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How symbolic links on the host filesystem are treated during an import.
+pub enum SymlinkPolicy {
+    /// Symbolic links are not imported.
+    Skip,
+
+    /// Symbolic links are imported as the file or folder they point to.
+    /// Links that point back to a folder currently being imported are skipped.
+    Follow,
+}
+
+#[derive(Debug, Clone)]
+/// Options controlling how much of the host filesystem is imported.
+pub struct ImportOptions {
+    /// The deepest level below the imported folder to import, where the entries of the
+    /// imported folder have a depth of 1. `None` imports everything.
+    pub max_depth: Option<usize>,
+
+    pub symlinks: SymlinkPolicy,
+
+    /// The maximum number of files to import. The import fails if the host folder has more.
+    pub max_files: Option<usize>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            symlinks: SymlinkPolicy::Skip,
+            max_files: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+/// Counts of what was imported.
+pub struct ImportSummary {
+    pub folders: usize,
+    pub files: usize,
+
+    /// Entries that were not imported, such as symbolic links or special files.
+    pub skipped: usize,
+}
+
+#[derive(Debug)]
+/// An error produced while importing from the host filesystem.
+pub enum ImportError {
+    Io(PathBuf, io::Error),
+    NotADirectory(PathBuf),
+    InvalidTarget,
+    AlreadyExists(String),
+    TooManyFiles(usize),
+    NoSpace,
+
+    /// An entry has a name the policy of the tree does not allow. Holds the reason.
+    InvalidName(PathBuf, String),
+
+    /// A file has a size the policy of the tree does not allow. Holds the reason.
+    InvalidSize(PathBuf, String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            ImportError::NotADirectory(path) => write!(f, "{} is not a directory", path.display()),
+            ImportError::InvalidTarget => write!(f, "The target path is not a folder in the tree"),
+            ImportError::AlreadyExists(name) => write!(f, "An item named {} already exists in the target folder", name),
+            ImportError::TooManyFiles(limit) => write!(f, "The import has more than {} files", limit),
+            ImportError::NoSpace => write!(f, "The import does not fit in the space left in the tree"),
+            ImportError::InvalidName(path, reason) => write!(f, "{}: {}", path.display(), reason),
+            ImportError::InvalidSize(path, reason) => write!(f, "{}: {}", path.display(), reason),
+        }
+    }
+}
+
+/// Convert a size in bytes into kilobytes, rounding up so that non-empty files are never 0KB.
+fn bytes_to_kilobytes(bytes: u64) -> usize {
+    bytes.div_ceil(1024) as usize
+}
+
+/// Walks the host filesystem building nodes, keeping track of the counts and options.
+struct Importer<'o> {
+    options: &'o ImportOptions,
+
+    /// The policy of the tree, which every imported entry must follow.
+    policy: Policy,
+    summary: ImportSummary,
+
+    /// Whether the tree ignores case in names. The imported folders are not in the tree
+    /// until the import is done, so they cannot check this themselves.
+    case_insensitive: bool,
+
+    /// Canonical paths of the folders currently being walked, used to detect symlink cycles.
+    ancestors: Vec<PathBuf>,
+}
+
+impl Importer<'_> {
+    /// Import the entries of the host folder at `path` into `folder`.
+    /// `depth` is the depth of the entries of the folder.
+    fn import_entries(&mut self, path: &Path, folder: &Rc<Node>, depth: usize) -> Result<(), ImportError> {
+        if self.options.max_depth.is_some_and(|max_depth| depth > max_depth) {
+            return Ok(());
+        }
+
+        let canonical = fs::canonicalize(path).map_err(|e| ImportError::Io(path.to_path_buf(), e))?;
+        self.ancestors.push(canonical);
+
+        let mut entries = fs::read_dir(path)
+            .map_err(|e| ImportError::Io(path.to_path_buf(), e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ImportError::Io(path.to_path_buf(), e))?;
+
+        // sort the entries so imports are the same on every host.
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let entry_path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            let mut metadata = fs::symlink_metadata(&entry_path)
+                .map_err(|e| ImportError::Io(entry_path.clone(), e))?;
+
+            if metadata.file_type().is_symlink() {
+                match self.options.symlinks {
+                    SymlinkPolicy::Skip => {
+                        self.summary.skipped += 1;
+                        continue;
+                    },
+                    SymlinkPolicy::Follow => match fs::metadata(&entry_path) {
+                        Ok(target_metadata) => metadata = target_metadata,
+                        // the link is broken.
+                        Err(_) => {
+                            self.summary.skipped += 1;
+                            continue;
+                        }
+                    },
+                }
+            }
+
+            tree::check_node_name(&name)
+                .map_err(|reason| ImportError::InvalidName(entry_path.clone(), reason))?;
+            if self.has_child(folder, &name) {
+                return Err(ImportError::AlreadyExists(name));
+            }
+
+            if metadata.is_dir() {
+                let canonical = fs::canonicalize(&entry_path)
+                    .map_err(|e| ImportError::Io(entry_path.clone(), e))?;
+                if self.ancestors.contains(&canonical) {
+                    self.summary.skipped += 1;
+                    continue;
+                }
+
+                self.policy.naming.check_folder_name(&name)
+                    .map_err(|reason| ImportError::InvalidName(entry_path.clone(), reason))?;
+
+                let child = Rc::new(Node::new_folder(&name));
+                self.import_entries(&entry_path, &child, depth + 1)?;
+                Rc::clone(folder).add(child).map_err(from_node_error)?;
+                self.summary.folders += 1;
+            } else if metadata.is_file() {
+                if self.options.max_files.is_some_and(|max_files| self.summary.files >= max_files) {
+                    return Err(ImportError::TooManyFiles(self.summary.files));
+                }
+
+                let size = bytes_to_kilobytes(metadata.len());
+                self.policy.naming.check_file_name(&name)
+                    .map_err(|reason| ImportError::InvalidName(entry_path.clone(), reason))?;
+                self.policy.limits.check_file_size(size)
+                    .map_err(|reason| ImportError::InvalidSize(entry_path.clone(), reason))?;
+
+                let child = Rc::new(Node::new_file(&name, size));
+                Rc::clone(folder).add(child).map_err(from_node_error)?;
+                self.summary.files += 1;
+            } else {
+                self.summary.skipped += 1;
+            }
+        }
+
+        self.ancestors.pop();
+        Ok(())
+    }
+
+    /// Check if `folder` already has a child named `name`, following the case rules of the tree.
+    fn has_child(&self, folder: &Node, name: &str) -> bool {
+        folder.children().unwrap().borrow().iter().any(|child| {
+            let other = child.name().unwrap();
+            if self.case_insensitive {
+                other.to_lowercase() == name.to_lowercase()
+            } else {
+                other == name
+            }
+        })
+    }
+}
+
+/// Convert an error from adding an imported node to the tree.
+fn from_node_error(e: NodeError) -> ImportError {
+    match e {
+        NodeError::AlreadyExists(name) => ImportError::AlreadyExists(name),
+        NodeError::NoSpace => ImportError::NoSpace,
+        NodeError::WrongType => ImportError::InvalidTarget,
+    }
+}
+
+/// Import the folder at `host_path` on the host filesystem into the folder at `target` in
+/// the tree. The imported folder keeps its name and is added as a new child of `target`.
+/// Every imported entry must follow the policy of the tree.
+/// Nothing is added to the tree if the import fails.
+pub fn import_dir(
+    ctx: &Context,
+    host_path: &Path,
+    target: &NodePath,
+    options: &ImportOptions,
+) -> Result<ImportSummary, ImportError> {
+    let target = ctx.node_from_path(target).map_err(|_| ImportError::InvalidTarget)?;
+    if let Node::Root { .. } = *target {
+        return Err(ImportError::InvalidTarget);
+    }
+
+    if !host_path.is_dir() {
+        return Err(ImportError::NotADirectory(host_path.to_path_buf()));
+    }
+
+    // paths such as "." have no name of their own, so use the name of the canonical path.
+    let canonical = fs::canonicalize(host_path).map_err(|e| ImportError::Io(host_path.to_path_buf(), e))?;
+    let name = canonical.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or("host".to_string());
+
+    // `child` follows the naming rules of the tree, such as case-insensitive names.
+    if target.child(&name).is_some() {
+        return Err(ImportError::AlreadyExists(name));
+    }
+
+    let policy = ctx.policy();
+    policy.naming.check_folder_name(&name)
+        .map_err(|reason| ImportError::InvalidName(host_path.to_path_buf(), reason))?;
+
+    let mut importer = Importer {
+        options,
+        policy,
+        summary: ImportSummary::default(),
+        case_insensitive: target.is_case_insensitive(),
+        ancestors: Vec::new(),
+    };
+
+    let folder = Rc::new(Node::new_folder(&name));
+    importer.import_entries(host_path, &folder, 1)?;
+    ctx.add_node(target, folder).map_err(from_node_error)?;
+    importer.summary.folders += 1;
+
+    Ok(importer.summary)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::parser::NodePathSegment;
+    use crate::tree::build_tree;
+    use std::env;
+    use std::ops::Deref;
+    use std::process;
+
+    /// A path inside a folder on the host that only one test uses.
+    /// The folder and everything in it is removed when this is dropped.
+    pub(crate) struct HostPath(PathBuf);
+
+    impl HostPath {
+        /// Get the path `name` inside a new empty folder for the test called `test_name`.
+        /// Nothing is created at the path itself.
+        pub(crate) fn new(test_name: &str, name: &str) -> Self {
+            let folder = env::temp_dir().join(format!["{}-{}", test_name, process::id()]);
+            let _ = fs::remove_dir_all(&folder);
+            fs::create_dir_all(&folder).unwrap();
+            Self(folder.join(name))
+        }
+    }
+
+    impl Deref for HostPath {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for HostPath {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(self.0.parent().unwrap());
+        }
+    }
+
+    /// Create a folder on the host with a few files for a test to import.
+    fn host_folder(test_name: &str) -> HostPath {
+        let path = HostPath::new(&format!["import-{}", test_name], "sample");
+        fs::create_dir_all(path.join("nested/deeper")).unwrap();
+        fs::write(path.join("a.txt"), vec![0; 2048]).unwrap();
+        fs::write(path.join("nested/b.txt"), vec![0; 10]).unwrap();
+        fs::write(path.join("nested/deeper/c.txt"), vec![0; 1025]).unwrap();
+        path
+    }
+
+    #[test]
+    fn import_whole_folder() {
+        let host = host_folder("whole");
+        let ctx = build_tree("test_user");
+        let target = vec![NodePathSegment::Name("downloads".to_string())];
+
+        let summary = import_dir(&ctx, &host, &target, &ImportOptions::default()).unwrap();
+        assert_eq!(summary, ImportSummary { folders: 3, files: 3, skipped: 0 });
+
+        let sample = ctx.node_from_path(&vec![
+            NodePathSegment::Name("downloads".to_string()),
+            NodePathSegment::Name("sample".to_string()),
+        ]).unwrap();
+        assert_eq!(sample.size().unwrap(), 5);
+        assert_eq!(sample.depth(), 4);
+        assert!(ctx.verify_sizes().is_ok());
+
+        // importing the same folder again would create a duplicate name.
+        let result = import_dir(&ctx, &host, &target, &ImportOptions::default());
+        assert!(matches!(result, Err(ImportError::AlreadyExists(..))));
+    }
+
+    #[test]
+    fn import_name_clash_ignores_case() {
+        let host = host_folder("case");
+        let ctx = build_tree("test_user");
+        ctx.set_case_insensitive(true);
+        let target = vec![NodePathSegment::Name("downloads".to_string())];
+        let downloads = ctx.node_from_path(&target).unwrap();
+        ctx.add_node(Rc::clone(&downloads), Rc::new(Node::new_folder("SAMPLE"))).unwrap();
+
+        let result = import_dir(&ctx, &host, &target, &ImportOptions::default());
+        assert!(matches!(result, Err(ImportError::AlreadyExists(name)) if name == "sample"));
+
+        // names inside the imported folder follow the same rules.
+        ctx.remove_node(Rc::clone(&downloads), "SAMPLE").unwrap();
+        fs::write(host.join("A.txt"), [0]).unwrap();
+        let result = import_dir(&ctx, &host, &target, &ImportOptions::default());
+        assert!(matches!(result, Err(ImportError::AlreadyExists(name)) if name == "a.txt"));
+        assert!(downloads.children().unwrap().borrow().is_empty());
+    }
+
+    #[test]
+    fn import_follows_policy() {
+        let host = host_folder("policy");
+        let ctx = build_tree("test_user");
+        let target = vec![NodePathSegment::Name("downloads".to_string())];
+        let downloads = ctx.node_from_path(&target).unwrap();
+
+        fs::write(host.join("nested/empty.txt"), []).unwrap();
+        let result = import_dir(&ctx, &host, &target, &ImportOptions::default());
+        assert!(matches!(result, Err(ImportError::InvalidSize(path, _)) if path.ends_with("nested/empty.txt")));
+        assert!(downloads.children().unwrap().borrow().is_empty());
+
+        fs::remove_file(host.join("nested/empty.txt")).unwrap();
+        fs::write(host.join("my notes.txt"), [0]).unwrap();
+        let result = import_dir(&ctx, &host, &target, &ImportOptions::default());
+        assert!(matches!(result, Err(ImportError::InvalidName(path, _)) if path.ends_with("my notes.txt")));
+        assert!(downloads.children().unwrap().borrow().is_empty());
+
+        ctx.set_policy(Policy::permissive());
+        let summary = import_dir(&ctx, &host, &target, &ImportOptions::default()).unwrap();
+        assert_eq!(summary.files, 4);
+    }
+
+    #[test]
+    fn import_with_limits() {
+        let host = host_folder("limits");
+        let ctx = build_tree("test_user");
+        let target = vec![NodePathSegment::Name("downloads".to_string())];
+
+        let options = ImportOptions { max_depth: Some(1), ..ImportOptions::default() };
+        let summary = import_dir(&ctx, &host, &target, &options).unwrap();
+        assert_eq!(summary, ImportSummary { folders: 2, files: 1, skipped: 0 });
+
+        let ctx = build_tree("test_user");
+        let options = ImportOptions { max_files: Some(2), ..ImportOptions::default() };
+        let result = import_dir(&ctx, &host, &target, &options);
+        assert!(matches!(result, Err(ImportError::TooManyFiles(2))));
+
+        // a failed import leaves the tree untouched.
+        let downloads = ctx.node_from_path(&target).unwrap();
+        assert!(downloads.children().unwrap().borrow().is_empty());
+
+        ctx.set_capacity(Some(ctx.used_space() + 4));
+        let result = import_dir(&ctx, &host, &target, &ImportOptions::default());
+        assert!(matches!(result, Err(ImportError::NoSpace)));
+        assert!(downloads.children().unwrap().borrow().is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+use std::rc::Rc;
+
+use crate::Context;
+use crate::commands::{self, CommandError, CommandOutput, CommandRegistry, ExitStatus};
+use crate::lexer::Lexer;
+use crate::parser::{ParseError, Parser};
+
+/// Runs lines of input such as `cd music && ls` against a tree.
+pub struct Interpreter {
+    ctx: Rc<Context>,
+
+    /// The commands that can be run.
+    registry: CommandRegistry,
+}
+
+#[derive(Debug)]
+/// The results of running a line of input.
+pub struct LineResult {
+    /// The result of each command that ran, in order. Commands skipped by `&&` or `||` have
+    /// no result.
+    pub results: Vec<Result<CommandOutput, CommandError>>,
+
+    /// The exit status of the last command that ran.
+    pub status: ExitStatus,
+}
+
+impl Interpreter {
+    /// Create an interpreter that runs the built in commands against the tree held by `ctx`.
+    pub fn new(ctx: Context) -> Self {
+        Self::with_registry(ctx, CommandRegistry::default())
+    }
+
+    /// Create an interpreter that runs the commands in `registry` against the tree held by `ctx`.
+    pub fn with_registry(ctx: Context, registry: CommandRegistry) -> Self {
+        Self {
+            ctx: Rc::new(ctx),
+            registry,
+        }
+    }
+
+    /// Get the commands that can be run.
+    pub fn registry(&self) -> &CommandRegistry {
+        &self.registry
+    }
+
+    /// Get the commands that can be run so new commands can be registered.
+    pub fn registry_mut(&mut self) -> &mut CommandRegistry {
+        &mut self.registry
+    }
+
+    /// Get the context holding the tree.
+    pub fn context(&self) -> &Context {
+        &self.ctx
+    }
+
+    /// Run a line of input and collect the result of every command that ran.
+    /// Fails without running anything if the line is not valid. The span of the error is a
+    /// range of bytes in `line`.
+    pub fn run_line(&self, line: &str) -> Result<LineResult, ParseError> {
+        let mut results = Vec::new();
+        let status = self.run_line_with(line, |result| results.push(result))?;
+        Ok(LineResult { results, status })
+    }
+
+    /// Run a line of input, passing the result of each command to `report` as soon as it
+    /// finishes. Fails without running anything if the line is not valid.
+    pub fn run_line_with(
+        &self,
+        line: &str,
+        report: impl FnMut(Result<CommandOutput, CommandError>),
+    ) -> Result<ExitStatus, ParseError> {
+        let tokens = Lexer::new(line).tokenize();
+        let commands = Parser::new(tokens, &self.registry).generate_commands()?;
+        Ok(commands::execute_chain(&self.ctx, &commands, report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Span;
+    use crate::parser::SyntaxError;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn run_lines() {
+        let interpreter = Interpreter::new(build_tree("test_user"));
+
+        let line = interpreter.run_line("cd documents && ls").unwrap();
+        assert_eq!(line.status, ExitStatus::Success);
+        assert_eq!(line.results.len(), 2);
+        assert_eq!(line.results[1].as_ref().unwrap().lines(), ["cv.pdf 1KB", "data.dat 1KB"]);
+        assert_eq!(interpreter.context().current_dir().to_string(), "/home/test_user/documents");
+
+        let line = interpreter.run_line("cd missing && ls").unwrap();
+        assert_eq!(line.status, ExitStatus::Failure);
+        assert_eq!(line.results.len(), 1);
+
+        assert!(matches!(interpreter.run_line("cd a b"), Err(ParseError { error: SyntaxError::InvalidArguments, .. })));
+        assert!(matches!(
+            interpreter.run_line("touch a.txt 99999999999999999999999"),
+            Err(ParseError { error: SyntaxError::InvalidNumber(..), span: Span { start: 12, end: 35 }, .. }),
+        ));
+        assert!(matches!(interpreter.run_line("format c"), Err(ParseError { error: SyntaxError::UnknownCommand(..), .. })));
+    }
+
+    #[test]
+    fn keywords_as_names() {
+        let interpreter = Interpreter::new(build_tree("test_user"));
+        let names: Vec<String> = interpreter.registry().commands().map(|(name, _)| name.to_string()).collect();
+
+        for name in names.iter() {
+            let line = format!["mkdir {0} && mkdir {0}x && cd {0}x && cd ../{0} && cd ..", name];
+            let result = interpreter.run_line(&line).unwrap();
+
+            assert_eq!(result.status, ExitStatus::Success, "{}", line);
+            assert_eq!(result.results.len(), 5);
+            assert!(interpreter.context().current_dir().child(name).is_some());
+        }
+    }
+
+    #[test]
+    fn render_errors() {
+        let interpreter = Interpreter::new(build_tree("test_user"));
+
+        let error = interpreter.run_line("ls && cd..").unwrap_err();
+        assert_eq!(error.render("ls && cd.."), [
+            "ls && cd..",
+            "      ^^^^",
+            "cd..: command not found",
+            "hint: did you mean `cd ..`?",
+        ].join("\n"));
+
+        let error = interpreter.run_line("  mkdri docs").unwrap_err();
+        assert_eq!(error.span, Span { start: 2, end: 7 });
+        assert_eq!(error.hint.as_deref(), Some("did you mean `mkdir`?"));
+
+        let error = interpreter.run_line("cd music/ ...").unwrap_err();
+        assert_eq!(error.render("cd music/ ..."), [
+            "cd music/ ...",
+            "            ^",
+            "Unexpected token in input",
+            "hint: use `..` for the parent folder, such as `cd ..`",
+        ].join("\n"));
+
+        let error = interpreter.run_line("rm -x a.txt").unwrap_err();
+        assert_eq!(error.span, Span { start: 0, end: 11 });
+        assert!(error.hint.unwrap().starts_with("usage: rm"));
+    }
+}
@@ -1,42 +1,78 @@
-use crate::commands;
+use crate::glob;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[allow(clippy::enum_variant_names)]
 /// Represents a token in the inputted command.
 pub enum Token {
-    Command(commands::CommandType),
+    /// The name of a command. Any word at the start of a command is a command name.
+    Command(String),
     Word(String),
+
+    /// A word with wildcards such as `*.mp3` or `[0-9]`, which matches the names in the tree.
+    /// A quoted word is never a glob, so `"*.mp3"` is a `Word`.
+    Glob(String),
     PreviousDir,
     Space,
     Dot,
     Slash,
     And,
+    Or,
+    Pipe,
+    Semicolon,
     Number(usize),
+    /// A number too large to be held, with its digits.
+    InvalidNumber(String),
+    /// A command option such as `-L` or `--max-depth`, including its dashes.
+    Flag(String),
     UnexpectedToken(char),
 }
 
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+/// A range of bytes in the input, from `start` up to but not including `end`.
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/// A `Token` and the part of the input it was read from.
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
 /// A `Lexer` is responsible for turning the raw input command from the user into a vector of
 /// tokens that can be interpereted at a later stage.
 pub struct Lexer<'s> {
     /// Input string
     input: &'s str,
 
-    /// Current character position in the input string.
+    /// Current byte position in the input string.
     cursor: usize,
+
+    /// Whether the next word is the name of a command, which is at the start of the input
+    /// and after each operator.
+    command_position: bool,
 }
 
 impl<'s> Lexer<'s> {
     /// Create a new `Lexer`.
-    /// Takes in the input source text
+    /// Takes in the input source text. Whitespace around the input is skipped, and spans are
+    /// byte offsets into `input` as it was given.
     pub fn new(input: &'s str) -> Self {
-        Self { input, cursor: 0 }
+        let input = input.trim_end();
+        let cursor = input.len() - input.trim_start().len();
+        Self { input, cursor, command_position: true }
     }
     
     /// Converts `self.input` into a vector of meaningful tokens.
-    pub fn tokenize(&mut self) -> Vec<Token> {
+    pub fn tokenize(&mut self) -> Vec<SpannedToken> {
         let mut accumulator = Vec::new();
 
+        let mut start = self.cursor;
         while let Some(token) = self.read_next_token() {
-            accumulator.push(token);
+            accumulator.push(SpannedToken { token, span: Span { start, end: self.cursor } });
+            start = self.cursor;
         }
 
         accumulator
@@ -50,25 +86,32 @@ impl<'s> Lexer<'s> {
 
         // check for tokens with more than 1 character.
         if self.check_multi_token("&&") {
+            self.command_position = true;
             return Some(Token::And);
+        } else if self.check_multi_token("||") {
+            self.command_position = true;
+            return Some(Token::Or);
         } else if self.check_multi_token("..") {
             return Some(Token::PreviousDir);
-        } else if self.check_multi_token("touch") {
-            return Some(Token::Command(commands::CommandType::Touch));
-        } else if self.check_multi_token("cd") {
-            return Some(Token::Command(commands::CommandType::Cd));
-        } else if self.check_multi_token("ls") {
-            return Some(Token::Command(commands::CommandType::Ls));
-        } else if self.check_multi_token("mkdir") {
-            return Some(Token::Command(commands::CommandType::Mkdir));
-        } else if self.check_multi_token("rmdir") {
-            return Some(Token::Command(commands::CommandType::Rmdir));
-        } else if self.check_multi_token("rm") {
-            return Some(Token::Command(commands::CommandType::Rm));
+        }
+
+        // the first word of each command is its name, which is looked up when parsing.
+        // keywords are only recognised here, so names such as `cdrom` are always words.
+        if self.command_position {
+            let current_char = self.input[self.cursor..].chars().next().unwrap();
+            if current_char.is_alphabetic() {
+                let next = self.command_end_index();
+                let name = &self.input[self.cursor..next];
+                self.cursor = next;
+                self.command_position = false;
+                return Some(Token::Command(name.to_string()));
+            } else if current_char != ' ' {
+                self.command_position = false;
+            }
         }
 
         // check for tokens with 1 character.
-        match self.input.chars().nth(self.cursor).unwrap() {
+        match self.input[self.cursor..].chars().next().unwrap() {
             '.' => {
                 self.cursor += 1;
                 Some(Token::Dot)
@@ -77,6 +120,16 @@ impl<'s> Lexer<'s> {
                 self.cursor += 1;
                 Some(Token::Slash)
             }
+            ';' => {
+                self.cursor += 1;
+                self.command_position = true;
+                Some(Token::Semicolon)
+            }
+            '|' => {
+                self.cursor += 1;
+                self.command_position = true;
+                Some(Token::Pipe)
+            }
             ' ' => {
                 self.cursor += 1;
                 Some(Token::Space)
@@ -87,33 +140,59 @@ impl<'s> Lexer<'s> {
                 Some(Token::Word(word))
             }
             '0'..='9' => {
-                let n = self.get_number_token();
-                Some(Token::Number(n))
+                // digits that are part of a path, such as `2026/`, `1.mp3` or `v1.2`, are a word.
+                let next = self.next_token_index();
+                let in_path = self.input[..self.cursor].ends_with(['/', '.'])
+                    || matches![self.input[next..].chars().next(), Some('.' | '/')];
+
+                if in_path || !self.input[self.cursor..next].chars().all(|c| c.is_ascii_digit()) {
+                    return Some(self.read_word(next));
+                }
+
+                Some(self.get_number_token())
+            }
+            '-' => {
+                let next = self.next_token_index();
+                let flag = &self.input[self.cursor..next];
+                self.cursor = next;
+                Some(Token::Flag(flag.to_string()))
             }
             
             // any other token is treated as a `Word`
             // the start of the next token needs to be indentified to tell how long the word is.
+            // a `+` can start a word, such as the size `+5` given to `find`, and a wildcard can
+            // start a glob such as `*.mp3`.
             _ => {
-                let current_char = self.input.chars().nth(self.cursor).unwrap();
-                if current_char.is_alphabetic() {
+                let current_char = self.input[self.cursor..].chars().next().unwrap();
+                if current_char.is_alphabetic() || matches![current_char, '+' | '*' | '?' | '['] {
                     let next = self.next_token_index();
-                    let word_contents = &self.input[self.cursor..next];
-                    self.cursor = next;
-                    return Some(Token::Word(word_contents.to_string()));
+                    Some(self.read_word(next))
                 } else {
-                    self.cursor += 1;
-                    return Some(Token::UnexpectedToken(current_char))
+                    self.cursor += current_char.len_utf8();
+                    Some(Token::UnexpectedToken(current_char))
                 }
             }
         }
     }
 
+    /// Read the word from the cursor up to `end`, which is a `Glob` if it has any wildcards.
+    fn read_word(&mut self, end: usize) -> Token {
+        let word = self.input[self.cursor..end].to_string();
+        self.cursor = end;
+
+        if glob::is_pattern(&word) {
+            Token::Glob(word)
+        } else {
+            Token::Word(word)
+        }
+    }
+
     /// Get the full string within quotes. This assumes the cursor
     /// Is currently 1 character after a quote.
     fn get_in_quotes(&mut self) -> String {
         let mut acc = String::new();
 
-        while let Some(ch) = self.input.chars().nth(self.cursor) {
+        while let Some(ch) = self.input[self.cursor..].chars().next() {
             if ch != '\"' {
                 acc.push(ch);
             } else {
@@ -121,16 +200,17 @@ impl<'s> Lexer<'s> {
                 break;
             }
 
-            self.cursor += 1
+            self.cursor += ch.len_utf8();
         }
 
         acc
     }
 
     /// Get a full number, assuming the cursor is currently over the beginning of the number.
-    fn get_number_token(&mut self) -> usize {
+    /// A number that does not fit in a `usize` is an `InvalidNumber`.
+    fn get_number_token(&mut self) -> Token {
         let mut acc = String::new();
-        while let Some(ch) = self.input.chars().nth(self.cursor) {
+        while let Some(ch) = self.input[self.cursor..].chars().next() {
             if ch.is_ascii_digit() {
                 acc.push(ch);
             } else {
@@ -138,16 +218,15 @@ impl<'s> Lexer<'s> {
             }
             self.cursor += 1;
         }
-        acc.parse::<usize>().unwrap()
+        match acc.parse::<usize>() {
+            Ok(n) => Token::Number(n),
+            Err(_) => Token::InvalidNumber(acc),
+        }
     }
 
     /// Check if the cursor is currently at a multi-character long token: `token`
     fn check_multi_token(&mut self, token: &str) -> bool {
-        if self.cursor + token.len() > self.input.len() {
-            return false;
-        }
-        let chars = &self.input[self.cursor..(self.cursor + token.len())];
-        if chars == token {
+        if self.input[self.cursor..].starts_with(token) {
             self.cursor += token.len();
             return true;
         }
@@ -155,29 +234,48 @@ impl<'s> Lexer<'s> {
         false
     }
 
+    /// Get the index of the end of a command name. A name runs until whitespace or an operator
+    /// so that input such as `cd.txt` is one unknown name rather than `cd` and a path.
+    fn command_end_index(&self) -> usize {
+        let chars = self.input[self.cursor..].char_indices();
+        for (i, c) in chars {
+            if matches![c, '&' | '|' | ';' | ' '] {
+                return self.cursor + i;
+            }
+        }
+
+        self.input.len()
+    }
+
     /// Get the starting index of the next token that isn't a word.
     fn next_token_index(&self) -> usize {
-        let chars = self.input[self.cursor..].chars();
-        for (i, c) in chars.enumerate() {
-            if matches![c, '.' | '/' | '&' | ' '] {
+        let chars = self.input[self.cursor..].char_indices();
+        for (i, c) in chars {
+            if matches![c, '.' | '/' | '&' | '|' | ';' | ' '] {
                 return self.cursor + i;
             }
         }
 
-        return self.input.len();
+        self.input.len()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::CommandRegistry;
+
+    /// Get the tokens without their spans.
+    fn kinds(tokens: Vec<SpannedToken>) -> Vec<Token> {
+        tokens.into_iter().map(|token| token.token).collect()
+    }
 
     #[test]
     fn tokenize_with_file() {
         let input = "touch folder1/folder2/file.png";
 
         let expected_tokens = vec![
-            Token::Command(commands::CommandType::Touch),
+            Token::Command(String::from("touch")),
             Token::Space,
             Token::Word(String::from("folder1")),
             Token::Slash,
@@ -189,7 +287,7 @@ mod tests {
         ];
 
         let mut command_lexer = Lexer::new(input);
-        let tokens = command_lexer.tokenize();
+        let tokens = kinds(command_lexer.tokenize());
 
         assert_eq!(tokens, expected_tokens);
     }
@@ -199,7 +297,7 @@ mod tests {
         let input = "cd folder1/folder2";
 
         let expected_tokens = vec![
-            Token::Command(commands::CommandType::Cd),
+            Token::Command(String::from("cd")),
             Token::Space,
             Token::Word(String::from("folder1")),
             Token::Slash,
@@ -207,7 +305,7 @@ mod tests {
         ];
 
         let mut command_lexer = Lexer::new(input);
-        let tokens = command_lexer.tokenize();
+        let tokens = kinds(command_lexer.tokenize());
 
         assert_eq!(tokens, expected_tokens);
     }
@@ -217,7 +315,7 @@ mod tests {
         let input = "cd ../folder1/folder2/file.png";
 
         let expected_tokens = vec![
-            Token::Command(commands::CommandType::Cd),
+            Token::Command(String::from("cd")),
             Token::Space,
             Token::PreviousDir,
             Token::Slash,
@@ -231,7 +329,7 @@ mod tests {
         ];
 
         let mut command_lexer = Lexer::new(input);
-        let tokens = command_lexer.tokenize();
+        let tokens = kinds(command_lexer.tokenize());
 
         assert_eq!(tokens, expected_tokens);
     }
@@ -241,7 +339,7 @@ mod tests {
         let input = "cd folder1/folder2 && touch file.png";
 
         let expected_tokens = vec![
-            Token::Command(commands::CommandType::Cd),
+            Token::Command(String::from("cd")),
             Token::Space,
             Token::Word(String::from("folder1")),
             Token::Slash,
@@ -249,7 +347,7 @@ mod tests {
             Token::Space,
             Token::And,
             Token::Space,
-            Token::Command(commands::CommandType::Touch),
+            Token::Command(String::from("touch")),
             Token::Space,
             Token::Word(String::from("file")),
             Token::Dot,
@@ -257,7 +355,7 @@ mod tests {
         ];
 
         let mut command_lexer = Lexer::new(input);
-        let tokens = command_lexer.tokenize();
+        let tokens = kinds(command_lexer.tokenize());
 
         assert_eq!(tokens, expected_tokens);
     }
@@ -267,7 +365,7 @@ mod tests {
         let input = "rm file1.png folder1/file1.png";
 
         let expected_tokens = vec![
-            Token::Command(commands::CommandType::Rm),
+            Token::Command(String::from("rm")),
             Token::Space,
             Token::Word(String::from("file1")),
             Token::Dot,
@@ -281,7 +379,7 @@ mod tests {
         ];
 
         let mut command_lexer = Lexer::new(input);
-        let tokens = command_lexer.tokenize();
+        let tokens = kinds(command_lexer.tokenize());
 
         assert_eq!(tokens, expected_tokens);
     }
@@ -291,7 +389,7 @@ mod tests {
         let input = "touch file.png 232";
 
         let expected_tokens = vec![
-            Token::Command(commands::CommandType::Touch),
+            Token::Command(String::from("touch")),
             Token::Space,
             Token::Word(String::from("file")),
             Token::Dot,
@@ -301,7 +399,71 @@ mod tests {
         ];
 
         let mut command_lexer = Lexer::new(input);
-        let tokens = command_lexer.tokenize();
+        let tokens = kinds(command_lexer.tokenize());
+
+        assert_eq!(tokens, expected_tokens);
+
+        let input = "touch file.png 99999999999999999999999";
+        let tokens = Lexer::new(input).tokenize();
+        let expected = SpannedToken {
+            token: Token::InvalidNumber("99999999999999999999999".to_string()),
+            span: Span { start: 15, end: 38 },
+        };
+        assert_eq!(tokens.last(), Some(&expected));
+    }
+
+    #[test]
+    fn tokenize_numbers_in_path() {
+        let input = "mkdir -p projects/2026/q4 && touch music/1.mp3 5";
+
+        let expected_tokens = vec![
+            Token::Command(String::from("mkdir")),
+            Token::Space,
+            Token::Flag(String::from("-p")),
+            Token::Space,
+            Token::Word(String::from("projects")),
+            Token::Slash,
+            Token::Word(String::from("2026")),
+            Token::Slash,
+            Token::Word(String::from("q4")),
+            Token::Space,
+            Token::And,
+            Token::Space,
+            Token::Command(String::from("touch")),
+            Token::Space,
+            Token::Word(String::from("music")),
+            Token::Slash,
+            Token::Word(String::from("1")),
+            Token::Dot,
+            Token::Word(String::from("mp3")),
+            Token::Space,
+            Token::Number(5),
+        ];
+
+        let mut command_lexer = Lexer::new(input);
+        let tokens = kinds(command_lexer.tokenize());
+
+        assert_eq!(tokens, expected_tokens);
+    }
+
+    #[test]
+    fn tokenize_flags() {
+        let input = "import -L --max-depth 2 folder";
+
+        let expected_tokens = vec![
+            Token::Command(String::from("import")),
+            Token::Space,
+            Token::Flag(String::from("-L")),
+            Token::Space,
+            Token::Flag(String::from("--max-depth")),
+            Token::Space,
+            Token::Number(2),
+            Token::Space,
+            Token::Word(String::from("folder")),
+        ];
+
+        let mut command_lexer = Lexer::new(input);
+        let tokens = kinds(command_lexer.tokenize());
 
         assert_eq!(tokens, expected_tokens);
     }
@@ -311,30 +473,139 @@ mod tests {
         let input = "mkdir \"this is a folder\"";
         
         let expected_tokens = vec![
-            Token::Command(commands::CommandType::Mkdir),
+            Token::Command(String::from("mkdir")),
             Token::Space,
             Token::Word(String::from("this is a folder")),
         ];
 
         let mut command_lexer = Lexer::new(input);
-        let tokens = command_lexer.tokenize();
+        let tokens = kinds(command_lexer.tokenize());
 
         assert_eq!(tokens, expected_tokens);
     }
     
+    #[test]
+    fn tokenize_globs() {
+        let input = "rm music/*.mp3 1? [a-c]é* \"*.txt\"";
+
+        let expected_tokens = vec![
+            Token::Command(String::from("rm")),
+            Token::Space,
+            Token::Word(String::from("music")),
+            Token::Slash,
+            Token::Glob(String::from("*")),
+            Token::Dot,
+            Token::Word(String::from("mp3")),
+            Token::Space,
+            Token::Glob(String::from("1?")),
+            Token::Space,
+            Token::Glob(String::from("[a-c]é*")),
+            Token::Space,
+            Token::Word(String::from("*.txt")),
+        ];
+
+        let mut command_lexer = Lexer::new(input);
+        let tokens = kinds(command_lexer.tokenize());
+
+        assert_eq!(tokens, expected_tokens);
+    }
+
     #[test]
     fn tokenize_unclosed_quote() {
         let input = "mkdir \"this is a folder";
 
         let expected_tokens = vec![
-            Token::Command(commands::CommandType::Mkdir),
+            Token::Command(String::from("mkdir")),
             Token::Space,
             Token::Word(String::from("this is a folder")),
         ];
 
         let mut command_lexer = Lexer::new(input);
-        let tokens = command_lexer.tokenize();
+        let tokens = kinds(command_lexer.tokenize());
+
+        assert_eq!(tokens, expected_tokens);
+    }
+
+    #[test]
+    fn tokenize_operators() {
+        let input = "cd a || ls;undo";
+
+        let expected_tokens = vec![
+            Token::Command(String::from("cd")),
+            Token::Space,
+            Token::Word(String::from("a")),
+            Token::Space,
+            Token::Or,
+            Token::Space,
+            Token::Command(String::from("ls")),
+            Token::Semicolon,
+            Token::Command(String::from("undo")),
+        ];
+
+        let mut command_lexer = Lexer::new(input);
+        let tokens = kinds(command_lexer.tokenize());
 
         assert_eq!(tokens, expected_tokens);
     }
+
+    #[test]
+    fn tokenize_keywords_as_names() {
+        for (keyword, _) in CommandRegistry::default().commands() {
+            let input = format!["mkdir {0}rom && touch {0}a.txt {0}_data/{0}", keyword];
+
+            let expected_tokens = vec![
+                Token::Command(String::from("mkdir")),
+                Token::Space,
+                Token::Word(format!["{}rom", keyword]),
+                Token::Space,
+                Token::And,
+                Token::Space,
+                Token::Command(String::from("touch")),
+                Token::Space,
+                Token::Word(format!["{}a", keyword]),
+                Token::Dot,
+                Token::Word(String::from("txt")),
+                Token::Space,
+                Token::Word(format!["{}_data", keyword]),
+                Token::Slash,
+                Token::Word(keyword.to_string()),
+            ];
+
+            let mut command_lexer = Lexer::new(&input);
+            let tokens = kinds(command_lexer.tokenize());
+
+            assert_eq!(tokens, expected_tokens, "{}", input);
+        }
+    }
+
+    #[test]
+    fn tokenize_whole_command_names() {
+        let input = "cdrom a;rmold.txt";
+
+        let expected_tokens = vec![
+            Token::Command(String::from("cdrom")),
+            Token::Space,
+            Token::Word(String::from("a")),
+            Token::Semicolon,
+            Token::Command(String::from("rmold.txt")),
+        ];
+
+        let mut command_lexer = Lexer::new(input);
+        let tokens = kinds(command_lexer.tokenize());
+
+        assert_eq!(tokens, expected_tokens);
+    }
+
+    #[test]
+    fn tokenize_spans() {
+        let input = "  cd \"é/x\" && ls ";
+
+        let mut command_lexer = Lexer::new(input);
+        let spans: Vec<(usize, usize)> = command_lexer.tokenize().iter()
+            .map(|token| (token.span.start, token.span.end))
+            .collect();
+
+        assert_eq!(spans, [(2, 4), (4, 5), (5, 11), (11, 12), (12, 14), (14, 15), (15, 17)]);
+        assert_eq!(&input[5..11], "\"é/x\"");
+    }
 }
@@ -0,0 +1,22 @@
+//! A simulated file tree with a small shell for changing it.
+//!
+//! The tree is held by a [`Context`] and changed by running commands through an
+//! [`Interpreter`], or by calling the methods on [`Context`] and [`Node`] directly.
+
+pub mod ast;
+pub mod commands;
+pub mod export;
+pub mod glob;
+pub mod history;
+pub mod import;
+pub mod lexer;
+pub mod parser;
+pub mod persist;
+pub mod policy;
+pub mod snapshot;
+pub mod tree;
+
+mod interpreter;
+
+pub use interpreter::{Interpreter, LineResult};
+pub use tree::{Context, Node};
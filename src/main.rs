@@ -1,79 +1,111 @@
-mod commands;
-
-mod tree;
-pub use tree::Context;
-
-mod parser;
-use parser::{Parser, SyntaxError};
-
-mod lexer;
-use lexer::Lexer;
+use assessment_2025::{Context, Interpreter, history, persist, tree};
+use assessment_2025::policy::Policy;
 
+use std::env;
 use std::io::{Write, stdin, stdout};
-use std::rc::Rc;
+use std::path::Path;
+use std::process;
 
 /// Helper function for reading a line of input.
+/// Returns `None` once the input has ended.
 fn get_user_input() -> Option<String> {
     stdout().flush().ok()?;
 
     let mut buffer = String::new();
-    stdin().read_line(&mut buffer).ok()?;
-
-    Some(buffer)
+    match stdin().read_line(&mut buffer).ok()? {
+        0 => None,
+        _ => Some(buffer),
+    }
 }
 
-/// Helper function to print error messages if a command cannot be built.
-fn handle_err(e: SyntaxError) {
-    match e {
-        SyntaxError::CommandNotProvided => {
-            println!("Please provide a command");
-        },
-        SyntaxError::InvalidCommand => {
-            println!("The provided command is not valid");
-        },
-        SyntaxError::InvalidPath => {
-            println!("The provided path is not valid");
-        },
-        SyntaxError::UnexpectedToken => {
-            println!("Unexpected token in input");
-        },
-        SyntaxError::InvalidArguments => {
-            println!("Arguments to the command are not valid");
-        },
-        SyntaxError::InvalidType => {
-            println!("The type of an argument is not valid");
+/// Helper function for building the starting tree.
+/// The tree is loaded from the file given with `--tree <file>`, otherwise the hardcoded tree is used.
+/// `--history <n>` sets how many changes can be undone and `--case-insensitive` makes names
+/// that only differ by case the same name. `--policy <preset>` or `--policy-file <file>` sets the
+/// naming rules and size limits, replacing the policy saved with the tree.
+fn initial_context() -> Result<Context, String> {
+    let mut args = env::args().skip(1);
+
+    let mut tree_file = None;
+    let mut history_depth = history::DEFAULT_HISTORY_DEPTH;
+    let mut case_insensitive = false;
+    let mut policy = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tree" => tree_file = Some(args.next().ok_or("--tree requires a file")?),
+            "--history" => {
+                history_depth = args.next()
+                    .and_then(|depth| depth.parse().ok())
+                    .ok_or("--history requires a number")?;
+            },
+            "--case-insensitive" => case_insensitive = true,
+            "--policy" => {
+                let name = args.next().ok_or("--policy requires a preset name")?;
+                policy = Some(Policy::preset(&name).map_err(|e| e.to_string())?);
+            },
+            "--policy-file" => {
+                let file = args.next().ok_or("--policy-file requires a file")?;
+                policy = Some(Policy::load_from_file(Path::new(&file))
+                    .map_err(|e| format!["Could not load the policy from {}: {}", file, e])?);
+            },
+            _ => return Err(format!["Unknown option: {}", arg]),
         }
     }
+
+    let ctx = match tree_file {
+        Some(file) => persist::load_from_file(Path::new(&file))
+            .map_err(|e| format!["Could not load the tree from {}: {}", file, e])?,
+        None => tree::build_tree("user1"),
+    };
+
+    ctx.set_history_depth(history_depth);
+    if case_insensitive {
+        ctx.set_case_insensitive(true);
+    }
+    if let Some(policy) = policy {
+        ctx.set_policy(policy);
+    }
+    Ok(ctx)
+}
+
+/// Ask the user a yes or no question on the terminal.
+/// Anything other than an answer starting with `y` is a no.
+fn confirm(question: &str) -> bool {
+    print!("{} (y/n) ", question);
+    get_user_input().is_some_and(|answer| answer.trim().to_lowercase().starts_with('y'))
 }
 
 fn main() {
     // create the main context
-    let ctx = Rc::new(tree::build_tree("user1"));
+    let interpreter = match initial_context() {
+        Ok(ctx) => Interpreter::new(ctx),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    interpreter.context().set_prompt(confirm);
 
     loop {
-        print!("{}> ", ctx.current_dir().borrow());
-        let input = get_user_input();
-
-        if let Some(input) = input {
-            // create a lexer and tokenize the input string
-            let mut command_lexer = Lexer::new(&input.trim());
-            let tokens = command_lexer.tokenize();
+        print!("{}> ", interpreter.context().current_dir());
+        let Some(input) = get_user_input() else {
+            // finish the prompt line when the input ends.
+            println!();
+            break;
+        };
 
-            // pass the token array from the lexer to the parser to generate the commands
-            let mut parser = Parser::new(tokens);
-            let commands = parser.generate_commands();
-
-            // execute the commands if they are valid.
-            match commands {
-                Ok(commands) => {
-                    for command in commands.iter() {
-                        command.execute(ctx.clone())
-                    }
-                },
-                Err(e) => {
-                    handle_err(e);
+        // run the commands, printing the result of each as it finishes.
+        let result = interpreter.run_line_with(&input, |result| match result {
+            Ok(output) => {
+                for line in output.lines() {
+                    println!("{}", line);
                 }
-            }
+            },
+            Err(e) => println!("{}", e),
+        });
+
+        if let Err(e) = result {
+            println!("{}", e.render(input.trim_end()));
         }
     }
 }
@@ -1,23 +1,35 @@
-use crate::commands;
-use crate::lexer::Token;
+use std::fmt;
+
+use crate::ast::{AndOr, AndOrOperator, CommandList, Pipeline, SimpleCommand, Word};
+use crate::commands::{self, CommandRegistry};
+use crate::glob;
+use crate::lexer::{Span, SpannedToken, Token};
 
 /// A `Parser` parses a vector of tokens into meaningful executable commands.
-pub struct Parser {
+/// The tokens are first parsed into a `CommandList` with the grammar below, which is then
+/// turned into commands using the registry. A `NAME` in a path can be a glob such as `*.mp3`.
+///
+/// ```text
+/// command_list   := and_or (";" and_or)* [";"]
+/// and_or         := pipeline (("&&" | "||") pipeline)*
+/// pipeline       := simple_command ("|" simple_command)*
+/// simple_command := NAME (" "+ word)*
+/// word           := FLAG | NUMBER | path
+/// path           := ["/"] [component ("/" component)* ["/"]]
+/// component      := ".." | [NAME] ("." NAME)*
+/// ```
+pub struct Parser<'r> {
+    /// The commands that can be built, looked up by name.
+    registry: &'r CommandRegistry,
+
     /// index of the current token being parsed
     cursor: usize,
 
-    /// Current command builder that found arguments are being loaded into.
-    current_command: Option<commands::CommandBuilder>,
-
-    /// Previously parsed token.
-    /// This is used for validating the token ordering.
-    previous_token: Option<Token>,
-
-    /// The starting index of the argument currently being parsed.
-    arg_start: Option<usize>,
-
     /// The input tokens.
     tokens: Vec<Token>,
+
+    /// The span of each token in `tokens`, used to point at the input in errors.
+    spans: Vec<Span>,
 }
 
 #[derive(Debug)]
@@ -29,228 +41,608 @@ pub enum SyntaxError {
     UnexpectedToken,
     InvalidArguments,
     InvalidType,
+    InvalidFlag,
+
+    /// A number too large to be held, with its digits.
+    InvalidNumber(String),
+
+    /// A command name that is not in the registry.
+    UnknownCommand(String),
+
+    /// Commands joined with `|`, which cannot pass output to each other yet.
+    UnsupportedPipe,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyntaxError::CommandNotProvided => write!(f, "Please provide a command"),
+            SyntaxError::InvalidCommand => write!(f, "The provided command is not valid"),
+            SyntaxError::InvalidPath => write!(f, "The provided path is not valid"),
+            SyntaxError::UnexpectedToken => write!(f, "Unexpected token in input"),
+            SyntaxError::InvalidArguments => write!(f, "Arguments to the command are not valid"),
+            SyntaxError::InvalidType => write!(f, "The type of an argument is not valid"),
+            SyntaxError::InvalidFlag => write!(f, "The provided flag is not valid"),
+            SyntaxError::InvalidNumber(digits) => write!(f, "{} is too large a number", digits),
+            SyntaxError::UnknownCommand(name) => write!(f, "{}: command not found", name),
+            SyntaxError::UnsupportedPipe => write!(f, "Commands cannot be joined with | yet"),
+        }
+    }
+}
+
+#[derive(Debug)]
+/// A `SyntaxError` with the part of the input that caused it.
+pub struct ParseError {
+    pub error: SyntaxError,
+    pub span: Span,
+
+    /// A suggestion for fixing the input, if there is one.
+    pub hint: Option<String>,
+}
+
+impl ParseError {
+    /// Render the error below the line of input it came from, with carets under the part of
+    /// the line that caused it, followed by the hint.
+    pub fn render(&self, input: &str) -> String {
+        let mut acc = String::new();
+
+        if !input.trim().is_empty() {
+            let start = self.span.start.min(input.len());
+            let end = self.span.end.clamp(start, input.len());
+            let padding = input[..start].chars().count();
+            let width = input[start..end].chars().count().max(1);
+            acc.push_str(&format!["{}\n{}{}\n", input, " ".repeat(padding), "^".repeat(width)]);
+        }
+
+        acc.push_str(&self.error.to_string());
+        if let Some(hint) = &self.hint {
+            acc.push_str(&format!["\nhint: {}", hint]);
+        }
+
+        acc
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
 }
 
-impl Parser {
+impl<'r> Parser<'r> {
     /// Create a new `Parser`.
-    /// Takes in a vector of input `Token`s.
-    pub fn new(tokens: Vec<Token>) -> Self {
+    /// Takes in a vector of input `Token`s and the registry used to build the commands.
+    pub fn new(tokens: Vec<SpannedToken>, registry: &'r CommandRegistry) -> Self {
+        let (tokens, spans) = tokens.into_iter()
+            .map(|token| (token.token, token.span))
+            .unzip();
+
         Self {
+            registry,
             tokens,
-            current_command: None,
-            previous_token: None,
+            spans,
             cursor: 0,
-            arg_start: None,
         }
     }
 
-    /// Generate a vector of executable commands.
-    /// This can include 1 or more commands as commands can be chained with the && operator.
-    pub fn generate_commands(&mut self) -> Result<Vec<Box<dyn commands::Command>>, SyntaxError> {
-        if self.tokens.len() < 1 {
-            return Err(SyntaxError::CommandNotProvided);
+    /// Generate a vector of executable commands, each with the `Connector` joining it to the
+    /// command before it. This can include 1 or more commands as commands can be chained with
+    /// the `&&`, `||` and `;` operators.
+    pub fn generate_commands(&mut self) -> Result<CommandChain, ParseError> {
+        let list = self.parse()?;
+        let mut commands = Vec::new();
+
+        for and_or in list.items.iter() {
+            for (operator, pipeline) in and_or.pipelines() {
+                let connector = match operator {
+                    None => Connector::Always,
+                    Some(AndOrOperator::And) => Connector::And,
+                    Some(AndOrOperator::Or) => Connector::Or,
+                };
+
+                commands.push((connector, self.build_pipeline(pipeline)?));
+            }
         }
 
-        let mut commands = Vec::new();
+        Ok(commands)
+    }
 
-        for token in self.tokens.iter() {
-            // validate the order of the tokens for each token
-            self.validate_token_order()?;
+    /// Parse the tokens into a `CommandList` without building any commands.
+    pub fn parse(&mut self) -> Result<CommandList, ParseError> {
+        self.cursor = 0;
+        self.skip_spaces();
 
-            match token {
-                Token::Command(command_type) => {
-                    // create a new command
-                    self.current_command = Some(commands::CommandBuilder::new(command_type.clone()));
-                },
-                Token::And => {
-                    // attempt to build the command
-                    if let Some(command) = self.current_command.take() {
-                        commands.push(command.build()?);
+        if self.peek().is_none() {
+            return Err(self.error(SyntaxError::CommandNotProvided, Span::default()));
+        }
+
+        self.command_list()
+    }
+
+    /// command_list := and_or (";" and_or)* [";"]
+    fn command_list(&mut self) -> Result<CommandList, ParseError> {
+        let mut items = vec![self.and_or()?];
+
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some(Token::Semicolon) => {
+                    self.cursor += 1;
+                    self.skip_spaces();
+
+                    // a trailing `;` ends the list.
+                    if self.peek().is_some() {
+                        items.push(self.and_or()?);
                     }
                 },
-                Token::Space => {
-                    if let Some(arg_start) = self.arg_start {
-                        // attempt to create an argument out of the accumulated tokens
-                        let arg = compile_argument(&self.tokens[arg_start..self.cursor])?;
-
-                        self.current_command.as_mut().map(|command| {
-                            command.add_argument(arg);
-                            command
-                        });
-                        self.arg_start = None;
-                    }
-                }
-                Token::UnexpectedToken(token) => {
-                    println!("Unexpected Token '{}'", token);
-                }
-                _ => {
-                    // in the case where there are no tokens that perform
-                    // operations themselves, set the current cursor position as the
-                    // start of a new argument if there is no current argument being parsed.
-                    if let None = self.arg_start {
-                        self.arg_start = Some(self.cursor);
-                    }
-                }
+                Some(_) => return Err(self.unexpected()),
+                None => break,
             }
+        }
 
-            // after the final token, compile an argument if there is one and 
-            // attempt to build the command
-            if self.cursor == self.tokens.len() - 1 {
-                if let Some(arg_start) = self.arg_start {
-                    let arg = compile_argument(&self.tokens[arg_start..])?;
-
-                    self.current_command.as_mut().map(|command| {
-                        command.add_argument(arg);
-                        command
-                    });
-                    self.arg_start = None;
-                }
+        Ok(CommandList { items })
+    }
 
-                if let Some(command) = self.current_command.take() {
-                    commands.push(command.build()?);
-                }
+    /// and_or := pipeline (("&&" | "||") pipeline)*
+    fn and_or(&mut self) -> Result<AndOr, ParseError> {
+        let first = self.pipeline()?;
+        let mut rest = Vec::new();
+
+        loop {
+            self.skip_spaces();
+            let operator = match self.peek() {
+                Some(Token::And) => AndOrOperator::And,
+                Some(Token::Or) => AndOrOperator::Or,
+                _ => break,
+            };
+
+            self.cursor += 1;
+            self.skip_spaces();
+            rest.push((operator, self.pipeline()?));
+        }
+
+        Ok(AndOr { first, rest })
+    }
+
+    /// pipeline := simple_command ("|" simple_command)*
+    fn pipeline(&mut self) -> Result<Pipeline, ParseError> {
+        let mut commands = vec![self.simple_command()?];
+
+        loop {
+            self.skip_spaces();
+            if self.peek() != Some(&Token::Pipe) {
+                break;
             }
 
-            self.previous_token = Some(token.clone());
             self.cursor += 1;
+            self.skip_spaces();
+            commands.push(self.simple_command()?);
         }
 
-        // return the accumulated commands
-        return Ok(commands);
+        let span = Span { start: commands[0].span.start, end: commands[commands.len() - 1].span.end };
+        Ok(Pipeline { commands, span })
     }
 
-    /// Validate the position of the current token in relation to the previous token.
-    fn validate_token_order(&self) -> Result<(), SyntaxError> {
-        match self.previous_token {
-            Some(Token::Slash) => match self.tokens[self.cursor] {
-                Token::Word(..) | Token::And | Token::Space => Ok(()),
-                _ => Err(SyntaxError::UnexpectedToken),
-            },
-            Some(Token::PreviousDir) => {
-                if let Token::Slash = self.tokens[self.cursor] {
-                    Ok(())
-                } else {
-                    Err(SyntaxError::UnexpectedToken)
-                }
-            },
-            Some(Token::Dot) => {
-                if let Token::Word(..) = self.tokens[self.cursor] {
-                    Ok(())
-                } else {
-                    Err(SyntaxError::UnexpectedToken)
-                }
-            },
-            Some(Token::Word(..)) => match self.tokens[self.cursor] {
-                Token::And | Token::Slash | Token::Dot | Token::Space => Ok(()),
-                _ => Err(SyntaxError::UnexpectedToken),
-            },
-            Some(Token::Command(..)) => match self.tokens[self.cursor] {
-                Token::Space => Ok(()),
-                _ => Err(SyntaxError::UnexpectedToken),
-            },
-            Some(Token::Space) => match self.tokens[self.cursor] {
-                Token::Dot => Err(SyntaxError::UnexpectedToken),
-                _ => Ok(()),
-            },
-            Some(Token::And) => match self.tokens[self.cursor] {
-                Token::Command(..) | Token::Space => Ok(()),
-                _ => Err(SyntaxError::UnexpectedToken),
+    /// simple_command := NAME (" "+ word)*
+    fn simple_command(&mut self) -> Result<SimpleCommand, ParseError> {
+        let Some(Token::Command(name)) = self.peek() else {
+            // point at the operator when the line ends without a command, such as `ls &&`.
+            let index = self.cursor.min(self.tokens.len() - 1);
+            return Err(self.error_at(SyntaxError::InvalidCommand, index));
+        };
+
+        let name = name.clone();
+        let name_span = self.spans[self.cursor];
+        self.cursor += 1;
+
+        let mut words = Vec::new();
+        loop {
+            // words are separated from the name and each other by spaces.
+            let start = self.cursor;
+            self.skip_spaces();
+            if self.cursor == start || !self.peek().is_some_and(is_word_token) {
+                self.cursor = start;
+                break;
+            }
+
+            words.push(self.word()?);
+        }
+
+        let end = words.last().map(|word| word.span.end).unwrap_or(name_span.end);
+        Ok(SimpleCommand { name, name_span, words, span: Span { start: name_span.start, end } })
+    }
+
+    /// word := FLAG | NUMBER | path
+    fn word(&mut self) -> Result<Word, ParseError> {
+        let start = self.cursor;
+
+        match self.peek() {
+            Some(Token::Flag(..)) | Some(Token::Number(..)) => self.cursor += 1,
+            Some(Token::InvalidNumber(digits)) => {
+                return Err(self.error_at(SyntaxError::InvalidNumber(digits.clone()), self.cursor));
             },
-            Some(Token::Number(..)) => match self.tokens[self.cursor] {
-                Token::Space => Ok(()),
-                _ => Err(SyntaxError::UnexpectedToken),
+            _ => self.path()?,
+        }
+
+        // a word runs until a space or an operator.
+        if self.peek().is_some_and(is_word_token) {
+            return Err(self.unexpected());
+        }
+
+        Ok(Word {
+            tokens: self.tokens[start..self.cursor].to_vec(),
+            span: Span { start: self.spans[start].start, end: self.spans[self.cursor - 1].end },
+        })
+    }
+
+    /// path := ["/"] [component ("/" component)* ["/"]]
+    fn path(&mut self) -> Result<(), ParseError> {
+        if self.peek() == Some(&Token::Slash) {
+            self.cursor += 1;
+        }
+
+        while self.peek().is_some_and(is_component_start) {
+            self.component()?;
+
+            if self.peek() != Some(&Token::Slash) {
+                break;
             }
-            Some(Token::UnexpectedToken(..)) => Ok(()),
-            None => {
-                if let Token::Command(..) = self.tokens[self.cursor] {
-                    Ok(())
-                } else {
-                    Err(SyntaxError::InvalidCommand)
-                }
+            self.cursor += 1;
+        }
+
+        Ok(())
+    }
+
+    /// component := ".." | [NAME] ("." NAME)*
+    fn component(&mut self) -> Result<(), ParseError> {
+        if self.peek() == Some(&Token::PreviousDir) {
+            self.cursor += 1;
+            return Ok(());
+        }
+
+        if let Some(Token::Word(..) | Token::Glob(..)) = self.peek() {
+            self.cursor += 1;
+        }
+
+        // every dot is followed by more of the name, such as in `archive.tar.gz` or `.bashrc`.
+        while self.peek() == Some(&Token::Dot) {
+            self.extension()?;
+        }
+
+        Ok(())
+    }
+
+    /// Parse a `.` followed by the part of the name after it.
+    fn extension(&mut self) -> Result<(), ParseError> {
+        match self.tokens.get(self.cursor + 1) {
+            Some(Token::Word(..) | Token::Glob(..)) => {
+                self.cursor += 2;
+                Ok(())
             },
+            // point at the dot, as it is usually a mistyped `..`.
+            _ => Err(self.unexpected()),
         }
     }
+
+    /// Build the command in a pipeline. Only pipelines with one command can be built.
+    fn build_pipeline(&self, pipeline: &Pipeline) -> Result<Box<dyn commands::Command>, ParseError> {
+        if let [command] = pipeline.commands.as_slice() {
+            return self.build_command(command);
+        }
+
+        Err(self.error(SyntaxError::UnsupportedPipe, pipeline.span))
+    }
+
+    /// Build a command by looking up its name in the registry.
+    fn build_command(&self, command: &SimpleCommand) -> Result<Box<dyn commands::Command>, ParseError> {
+        let mut builder = commands::CommandBuilder::new(&command.name);
+        for word in command.words.iter() {
+            let argument = compile_argument(&word.tokens).map_err(|e| self.error(e, word.span))?;
+            builder.add_argument(argument);
+        }
+
+        builder.build(self.registry).map_err(|e| {
+            // point at the name of an unknown command, or at the whole command otherwise.
+            let span = match e {
+                SyntaxError::UnknownCommand(..) => command.name_span,
+                _ => command.span,
+            };
+
+            let mut error = self.error(e, span);
+            if error.hint.is_none() {
+                error.hint = self.registry.help(&command.name).map(|help| format!["usage: {}", help]);
+            }
+            error
+        })
+    }
+
+    /// Get the token at the cursor.
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.cursor)
+    }
+
+    /// Move the cursor past any spaces.
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(&Token::Space) {
+            self.cursor += 1;
+        }
+    }
+
+    /// Create an error for an unexpected token at the cursor.
+    fn unexpected(&self) -> ParseError {
+        self.error_at(SyntaxError::UnexpectedToken, self.cursor.min(self.tokens.len() - 1))
+    }
+
+    /// Create a `ParseError` for `error` at the token at `index`.
+    fn error_at(&self, error: SyntaxError, index: usize) -> ParseError {
+        let mut parse_error = self.error(error, self.spans[index]);
+
+        if let (SyntaxError::UnexpectedToken, Token::Dot) = (&parse_error.error, &self.tokens[index]) {
+            parse_error.hint = Some("use `..` for the parent folder, such as `cd ..`".to_string());
+        }
+        parse_error
+    }
+
+    /// Create a `ParseError` for `error` at `span`, with a hint if there is one.
+    fn error(&self, error: SyntaxError, span: Span) -> ParseError {
+        let hint = match &error {
+            SyntaxError::UnknownCommand(name) => suggest_command(self.registry, name),
+            SyntaxError::InvalidCommand => Some("start with the name of a command, such as `ls`".to_string()),
+            _ => None,
+        };
+
+        ParseError { error, span, hint }
+    }
 }
 
-#[derive(Debug)]
+/// Check if a token can be part of a word.
+fn is_word_token(token: &Token) -> bool {
+    !matches![token, Token::Space | Token::And | Token::Or | Token::Pipe | Token::Semicolon]
+}
+
+/// Check if a token can start a path component.
+fn is_component_start(token: &Token) -> bool {
+    matches![token, Token::Word(..) | Token::Glob(..) | Token::PreviousDir | Token::Dot]
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+/// How a command is joined to the command before it, which decides whether it runs.
+pub enum Connector {
+    /// The first command, or a command after `;`. It always runs.
+    Always,
+
+    /// A command after `&&`. It only runs if the previous command succeeded.
+    And,
+
+    /// A command after `||`. It only runs if the previous command failed.
+    Or,
+}
+
+impl Connector {
+    /// Check if a command joined with this connector should run after a command that
+    /// finished with `status`.
+    pub fn should_run(&self, status: commands::ExitStatus) -> bool {
+        match self {
+            Connector::Always => true,
+            Connector::And => status == commands::ExitStatus::Success,
+            Connector::Or => status == commands::ExitStatus::Failure,
+        }
+    }
+}
+
+/// Commands in the order they were written, each with the `Connector` joining it to the
+/// command before it.
+pub type CommandChain = Vec<(Connector, Box<dyn commands::Command>)>;
+
+#[derive(Debug, Clone)]
 pub enum Argument {
     Path(NodePath),
     Number(usize),
+    Flag(String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 /// Part of a NodePath
 pub enum NodePathSegment {
     Root,
-    Dir(String),
+
+    /// The name of a file or a folder. Which one it is comes from the tree or the command.
+    Name(String),
     Parent,
-    File(String),
+
+    /// A glob matching the names of files and folders, such as `*.mp3`. Any special characters
+    /// that were quoted are escaped, so this can be passed to `glob::matches`.
+    Pattern(String),
 }
 
 pub type NodePath = Vec<NodePathSegment>;
 
+/// Check if any segment of `path` is a glob. Commands expand globs when they run.
+pub fn is_glob_path(path: &[NodePathSegment]) -> bool {
+    path.iter().any(|segment| matches!(segment, NodePathSegment::Pattern(..)))
+}
+
+/// Convert a `NodePath` back into the text form it was parsed from.
+/// This is used by commands that take a path on the host filesystem.
+pub fn path_to_string(path: &NodePath) -> String {
+    let mut acc = String::new();
+
+    for (i, segment) in path.iter().enumerate() {
+        if i > 0 && !matches!(path[i - 1], NodePathSegment::Root) {
+            acc.push('/');
+        }
+
+        match segment {
+            NodePathSegment::Root => acc.push('/'),
+            NodePathSegment::Parent => acc.push_str(".."),
+            NodePathSegment::Name(name) | NodePathSegment::Pattern(name) => acc.push_str(name),
+        }
+    }
+
+    acc
+}
+
+/// Suggest a registered command for the unknown command `name`.
+fn suggest_command(registry: &CommandRegistry, name: &str) -> Option<String> {
+    // a command written without a space before its argument, such as `cd..`.
+    let joined = registry.commands()
+        .map(|(command, _)| command)
+        .filter(|command| {
+            name.strip_prefix(command)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(|c| !c.is_alphanumeric())
+        })
+        .max_by_key(|command| command.len());
+    if let Some(command) = joined {
+        return Some(format!["did you mean `{} {}`?", command, &name[command.len()..]]);
+    }
+
+    // a misspelled command, only suggested if it is close enough to be a typo.
+    registry.commands()
+        .map(|(command, _)| (edit_distance(name, command), command))
+        .filter(|(distance, _)| *distance <= 1 || (*distance == 2 && name.len() >= 5))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, command)| format!["did you mean `{}`?", command])
+}
+
+/// Count the characters that need to be inserted, removed or replaced to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a_char != *b_char);
+            current.push(replace.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
 /// Helper function for converting an array of `Token`s into a `NodePath`.
 /// Returns a `SyntaxError` if the path is not valid.
 fn compile_argument(tokens: &[Token]) -> Result<Argument, SyntaxError> {
-    match tokens.get(0) {
-        Some(Token::Word { .. }) | Some(Token::Slash) 
-        | Some(Token::PreviousDir) => {
-            return compile_path(tokens).map(|path| Argument::Path(path));
+    match tokens.first() {
+        Some(Token::Word(..)) | Some(Token::Glob(..)) | Some(Token::Slash)
+        | Some(Token::PreviousDir) | Some(Token::Dot) => {
+            compile_path(tokens).map(Argument::Path)
         },
         Some(Token::Number(n)) => {
-            return Ok(Argument::Number(*n));
+            Ok(Argument::Number(*n))
+        }
+        Some(Token::Flag(flag)) => {
+            Ok(Argument::Flag(flag.clone()))
         }
         _ => {
-            return Err(SyntaxError::UnexpectedToken);
+            Err(SyntaxError::UnexpectedToken)
         }
     }
 }
 
 fn compile_path(tokens: &[Token]) -> Result<NodePath, SyntaxError> {
     let mut path = Vec::new();
-    if let Some(Token::Slash) = tokens.get(0) {
+    let mut tokens_iter = tokens.iter().peekable();
+
+    if let Some(Token::Slash) = tokens_iter.peek() {
+        tokens_iter.next();
         path.push(NodePathSegment::Root);
     }
 
-    let mut tokens_iter = tokens.iter().peekable();
-    while let Some(token) = tokens_iter.next() {
+    // the name being read, which can be made of several words and dots such as `archive.tar.gz`.
+    let mut name: Option<NameBuilder> = None;
+
+    for token in tokens_iter {
         match token {
-            Token::Word(name) => path.push(NodePathSegment::Dir(name.clone())),
+            Token::Word(word) => name.get_or_insert_default().push_literal(word),
+            Token::Glob(pattern) => name.get_or_insert_default().push_pattern(pattern),
+            Token::Dot => name.get_or_insert_default().push_literal("."),
             Token::PreviousDir => path.push(NodePathSegment::Parent),
-            Token::Dot => {
-                let last = path.last_mut().ok_or(SyntaxError::InvalidPath)?;
-                let next = tokens_iter.peek().ok_or(SyntaxError::InvalidPath)?;
-
-                if let Token::Word(extension_name) = *next {
-                    match last { NodePathSegment::Dir(filename) => {
-                            *last = NodePathSegment::File(format!("{}.{}", filename, extension_name));
-                        },
-                        _ => path.push(NodePathSegment::File(format!(".{}", extension_name))),
-                    }
-                    break;
-                } else {
-                    return Err(SyntaxError::InvalidPath);
+            Token::Slash => {
+                if let Some(name) = name.take() {
+                    path.push(name.finish());
                 }
-            }
-            _ => continue,
+            },
+            _ => return Err(SyntaxError::InvalidPath),
         }
     }
 
+    if let Some(name) = name {
+        path.push(name.finish());
+    }
+
     Ok(path)
 }
 
+#[derive(Default)]
+/// A path segment being read from several tokens, such as `*.tar.gz`.
+struct NameBuilder {
+    name: String,
+
+    /// The same name as a glob, with the special characters of the literal parts escaped.
+    pattern: String,
+    is_pattern: bool,
+}
+
+impl NameBuilder {
+    fn push_literal(&mut self, text: &str) {
+        self.name.push_str(text);
+        self.pattern.push_str(&glob::escape(text));
+    }
+
+    fn push_pattern(&mut self, pattern: &str) {
+        self.name.push_str(pattern);
+        self.pattern.push_str(pattern);
+        self.is_pattern = true;
+    }
+
+    /// Get the segment, which is a `Pattern` if any part of it was a glob.
+    fn finish(self) -> NodePathSegment {
+        if self.is_pattern {
+            NodePathSegment::Pattern(self.pattern)
+        } else {
+            NodePathSegment::Name(self.name)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::lexer::Lexer;
 
+    /// Parse `input` into a `CommandList` without building the commands.
+    fn parse(input: &str) -> Result<CommandList, ParseError> {
+        let registry = CommandRegistry::default();
+        Parser::new(Lexer::new(input).tokenize(), &registry).parse()
+    }
+
+    /// Get the name and the text of each word of a command.
+    fn command_text<'i>(input: &'i str, command: &SimpleCommand) -> (String, Vec<&'i str>) {
+        let words = command.words.iter().map(|word| &input[word.span.start..word.span.end]).collect();
+        (command.name.clone(), words)
+    }
+
+    #[test]
+    fn test_path_to_string() {
+        let input = "save ../trees/sample.json";
+        let tokens: Vec<Token> = Lexer::new(input).tokenize().into_iter().map(|token| token.token).collect();
+        let path = compile_path(&tokens[2..]).unwrap();
+
+        assert_eq!(path_to_string(&path), "../trees/sample.json");
+        assert_eq!(path_to_string(&vec![NodePathSegment::Root, NodePathSegment::Name("tmp".to_string())]), "/tmp");
+    }
+
     #[test]
     fn test_multi_command() {
         let input = "cd folder1/folder2 && touch file.png";
         let mut cmd_lexer = Lexer::new(input);
         let tokens = cmd_lexer.tokenize();
 
-        let mut parser = Parser::new(tokens);
+        let registry = CommandRegistry::default();
+        let mut parser = Parser::new(tokens, &registry);
         let commands = parser.generate_commands();
 
         assert!(commands.is_ok());
@@ -262,10 +654,11 @@ mod tests {
         let mut cmd_lexer = Lexer::new(input);
         let tokens = cmd_lexer.tokenize();
 
-        let mut parser = Parser::new(tokens);
+        let registry = CommandRegistry::default();
+        let mut parser = Parser::new(tokens, &registry);
         let commands = parser.generate_commands();
 
-        assert!(!commands.is_ok());
+        assert!(commands.is_err());
     }
 
     #[test]
@@ -274,7 +667,8 @@ mod tests {
         let mut cmd_lexer = Lexer::new(input);
         let tokens = cmd_lexer.tokenize();
 
-        let mut parser = Parser::new(tokens);
+        let registry = CommandRegistry::default();
+        let mut parser = Parser::new(tokens, &registry);
         let commands = parser.generate_commands();
         
         assert!(commands.is_ok());
@@ -286,7 +680,8 @@ mod tests {
         let mut cmd_lexer = Lexer::new(input);
         let tokens = cmd_lexer.tokenize();
 
-        let mut parser = Parser::new(tokens);
+        let registry = CommandRegistry::default();
+        let mut parser = Parser::new(tokens, &registry);
         let commands = parser.generate_commands();
 
         assert!(commands.is_ok());
@@ -298,9 +693,94 @@ mod tests {
         let mut cmd_lexer = Lexer::new(input);
         let tokens = cmd_lexer.tokenize();
 
-        let mut parser = Parser::new(tokens);
+        let registry = CommandRegistry::default();
+        let mut parser = Parser::new(tokens, &registry);
         let commands = parser.generate_commands();
 
-        assert!(!commands.is_ok());
+        assert!(commands.is_err());
+    }
+
+    #[test]
+    fn grammar_command_list() {
+        let list = parse("mkdir a && cd a || ls ; undo;").unwrap();
+        assert_eq!(list.items.len(), 2);
+
+        let operators: Vec<Option<AndOrOperator>> = list.items[0].pipelines().map(|(operator, _)| operator).collect();
+        assert_eq!(operators, [None, Some(AndOrOperator::And), Some(AndOrOperator::Or)]);
+        assert_eq!(list.items[1].first.commands[0].name, "undo");
+    }
+
+    #[test]
+    fn grammar_words() {
+        let input = "  import -L --max-depth 2   ../host/ /  ";
+        let list = parse(input).unwrap();
+        let command = &list.items[0].first.commands[0];
+
+        assert_eq!(command_text(input, command), ("import".to_string(), vec!["-L", "--max-depth", "2", "../host/", "/"]));
+        assert_eq!(&input[command.span.start..command.span.end], "import -L --max-depth 2   ../host/ /");
+    }
+
+    #[test]
+    fn grammar_paths() {
+        for input in ["cd ..", "cd ../..", "cd ../music/", "cd /", "touch .hidden", "touch /a/b.txt", "ls \"my music\"", "touch v1.2.3", "cd .config/a.b/"] {
+            assert!(parse(input).is_ok(), "{}", input);
+        }
+
+        let list = parse("cd ../.. && ls").unwrap();
+        let tokens = &list.items[0].first.commands[0].words[0].tokens;
+        assert_eq!(compile_path(tokens).unwrap(), [NodePathSegment::Parent, NodePathSegment::Parent]);
+
+        // names without dots, with a leading dot and with several dots are all just names.
+        let list = parse("cp Makefile .config/archive.tar.gz").unwrap();
+        let words = &list.items[0].first.commands[0].words;
+        assert_eq!(compile_path(&words[0].tokens).unwrap(), [NodePathSegment::Name("Makefile".to_string())]);
+        assert_eq!(compile_path(&words[1].tokens).unwrap(), [
+            NodePathSegment::Name(".config".to_string()),
+            NodePathSegment::Name("archive.tar.gz".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn grammar_globs() {
+        let list = parse("rm music/*.mp3 \"a*\".?/b").unwrap();
+        let words = &list.items[0].first.commands[0].words;
+        assert_eq!(compile_path(&words[0].tokens).unwrap(), [
+            NodePathSegment::Name("music".to_string()),
+            NodePathSegment::Pattern("*.mp3".to_string()),
+        ]);
+
+        // quoted parts of a glob only match themselves.
+        assert_eq!(compile_path(&words[1].tokens).unwrap(), [
+            NodePathSegment::Pattern("a[*].?".to_string()),
+            NodePathSegment::Name("b".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn grammar_errors() {
+        let expect_error = |input: &str, start: usize| {
+            let error = parse(input).unwrap_err();
+            assert_eq!(error.span.start, start, "{}", input);
+            error.error
+        };
+
+        assert!(matches!(expect_error("", 0), SyntaxError::CommandNotProvided));
+        assert!(matches!(expect_error("ls &&", 3), SyntaxError::InvalidCommand));
+        assert!(matches!(expect_error("cd a.txt./b", 8), SyntaxError::UnexpectedToken));
+        assert!(matches!(expect_error("cd a//b", 5), SyntaxError::UnexpectedToken));
+        assert!(matches!(expect_error("touch a.txt -r5/x", 15), SyntaxError::UnexpectedToken));
+        assert!(matches!(expect_error("cd .", 3), SyntaxError::UnexpectedToken));
+        assert!(matches!(expect_error("ls ; ; ls", 5), SyntaxError::InvalidCommand));
+    }
+
+    #[test]
+    fn grammar_pipelines() {
+        let input = "ls | ls music";
+        let list = parse(input).unwrap();
+        assert_eq!(list.items[0].first.commands.len(), 2);
+
+        let registry = CommandRegistry::default();
+        let result = Parser::new(Lexer::new(input).tokenize(), &registry).generate_commands();
+        assert!(matches!(result, Err(ParseError { error: SyntaxError::UnsupportedPipe, .. })));
     }
 }
@@ -0,0 +1,288 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Context;
+use crate::policy::Policy;
+use crate::tree::{self, Node};
+
+/// The version of the document format written by `save`.
+/// Documents with a different version are rejected when loading.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+/// The top level of a saved tree.
+struct TreeDocument {
+    version: u32,
+
+    /// Names of the folders from the root to the current directory.
+    current_dir: Vec<String>,
+
+    /// The rules the tree follows. Trees saved without one follow the default policy.
+    #[serde(default)]
+    policy: Policy,
+
+    root: NodeDocument,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+/// A saved `Node`. Parent links and depths are not stored as they are rebuilt on load.
+enum NodeDocument {
+    Root {
+        #[serde(default)]
+        case_insensitive: bool,
+
+        children: Vec<NodeDocument>,
+    },
+    Folder {
+        name: String,
+
+        /// Folder sizes are written for readability but recalculated from the files on load.
+        #[serde(default)]
+        size: usize,
+
+        #[serde(default)]
+        children: Vec<NodeDocument>,
+    },
+    File {
+        name: String,
+        size: usize,
+    },
+}
+
+#[derive(Debug)]
+/// An error produced while saving or loading a tree.
+pub enum PersistError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+    InvalidDocument(String),
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistError::Io(e) => write!(f, "{}", e),
+            PersistError::Json(e) => write!(f, "Invalid JSON: {}", e),
+            PersistError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported tree version {} (expected {})", version, FORMAT_VERSION)
+            },
+            PersistError::InvalidDocument(reason) => write!(f, "Invalid tree: {}", reason),
+        }
+    }
+}
+
+impl From<io::Error> for PersistError {
+    fn from(e: io::Error) -> Self {
+        PersistError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for PersistError {
+    fn from(e: serde_json::Error) -> Self {
+        PersistError::Json(e)
+    }
+}
+
+/// Convert a node and all of its children into a `NodeDocument`.
+fn node_to_document(node: &Node) -> NodeDocument {
+    let children = || {
+        node.children().unwrap().borrow().iter()
+            .map(|child| node_to_document(child))
+            .collect()
+    };
+
+    match node {
+        Node::Root { .. } => NodeDocument::Root {
+            case_insensitive: node.is_case_insensitive(),
+            children: children(),
+        },
+        Node::Folder { .. } => NodeDocument::Folder {
+            name: node.name().unwrap(),
+            size: node.size().unwrap(),
+            children: children(),
+        },
+        Node::File { .. } => NodeDocument::File {
+            name: node.name().unwrap(),
+            size: node.size().unwrap(),
+        },
+    }
+}
+
+/// Build a node from a `NodeDocument`.
+/// Children are attached with `Node::add` so parent links, depths and folder sizes are rebuilt.
+/// Names that could not have been created in a tree are rejected.
+fn document_to_node(document: &NodeDocument) -> Result<Rc<Node>, PersistError> {
+    if let NodeDocument::Folder { name, .. } | NodeDocument::File { name, .. } = document {
+        tree::check_node_name(name).map_err(PersistError::InvalidDocument)?;
+    }
+
+    let (node, children) = match document {
+        NodeDocument::Root { case_insensitive, children } => {
+            let root = Node::new_root();
+            root.set_case_insensitive(*case_insensitive);
+            (Rc::new(root), children)
+        },
+        NodeDocument::Folder { name, children, .. } => (Rc::new(Node::new_folder(name)), children),
+        NodeDocument::File { name, size } => return Ok(Rc::new(Node::new_file(name, *size))),
+    };
+
+    for child in children {
+        if let NodeDocument::Root { .. } = child {
+            return Err(PersistError::InvalidDocument("the root can only be at the top of the tree".to_string()));
+        }
+        Rc::clone(&node).add(document_to_node(child)?)
+            .map_err(|e| PersistError::InvalidDocument(e.to_string()))?;
+    }
+
+    Ok(node)
+}
+
+/// Serialize the tree held by `ctx` into a JSON string.
+pub fn to_json(ctx: &Context) -> String {
+    let document = TreeDocument {
+        version: FORMAT_VERSION,
+        current_dir: ctx.current_dir().path_names(),
+        policy: ctx.policy(),
+        root: node_to_document(&ctx.root()),
+    };
+
+    serde_json::to_string_pretty(&document).unwrap()
+}
+
+/// Build a new `Context` from a JSON string produced by `to_json`.
+pub fn from_json(json: &str) -> Result<Context, PersistError> {
+    let document: TreeDocument = serde_json::from_str(json)?;
+
+    if document.version != FORMAT_VERSION {
+        return Err(PersistError::UnsupportedVersion(document.version));
+    }
+
+    if !matches!(document.root, NodeDocument::Root { .. }) {
+        return Err(PersistError::InvalidDocument("the top of the tree must be a root".to_string()));
+    }
+
+    if document.current_dir.is_empty() {
+        return Err(PersistError::InvalidDocument("the current directory cannot be the root".to_string()));
+    }
+
+    let root = document_to_node(&document.root)?;
+
+    // follow the saved folder names from the root to find the current directory.
+    let current_dir = Node::find_folder(&root, &document.current_dir)
+        .ok_or(PersistError::InvalidDocument("the current directory is not in the tree".to_string()))?;
+
+    let ctx = Context::new(root, current_dir);
+    ctx.set_policy(document.policy);
+    Ok(ctx)
+}
+
+/// Save the tree held by `ctx` to the file at `path`.
+pub fn save_to_file(ctx: &Context, path: &Path) -> Result<(), PersistError> {
+    fs::write(path, to_json(ctx))?;
+    Ok(())
+}
+
+/// Load a tree from the file at `path` into a new `Context`.
+pub fn load_from_file(path: &Path) -> Result<Context, PersistError> {
+    let json = fs::read_to_string(path)?;
+    from_json(&json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn round_trip() {
+        let ctx = build_tree("test_user");
+        let loaded = from_json(&to_json(&ctx)).unwrap();
+
+        assert_eq!(to_json(&ctx), to_json(&loaded));
+        assert_eq!(loaded.current_dir().to_string(), "/home/test_user");
+        assert!(loaded.verify_sizes().is_ok());
+    }
+
+    #[test]
+    fn round_trip_policy() {
+        let ctx = build_tree("test_user");
+        ctx.set_policy(Policy::dos83());
+
+        let loaded = from_json(&to_json(&ctx)).unwrap();
+        assert_eq!(loaded.policy(), Policy::dos83());
+    }
+
+    #[test]
+    fn load_rebuilds_links() {
+        let json = r#"{
+            "version": 1,
+            "current_dir": ["home"],
+            "root": { "kind": "root", "children": [
+                { "kind": "folder", "name": "home", "children": [
+                    { "kind": "folder", "name": "a", "children": [
+                        { "kind": "file", "name": "b.txt", "size": 4 }
+                    ] }
+                ] }
+            ] }
+        }"#;
+
+        let ctx = from_json(json).unwrap();
+        let home = ctx.current_dir();
+        let a = Rc::clone(&home.children().unwrap().borrow()[0]);
+        let b = Rc::clone(&a.children().unwrap().borrow()[0]);
+
+        assert_eq!(home.size().unwrap(), 4);
+        assert_eq!(b.depth(), 3);
+        assert_eq!(b.to_string(), "/home/a/b.txt");
+    }
+
+    #[test]
+    fn load_rejects_duplicate_names() {
+        let json = r#"{
+            "version": 1,
+            "current_dir": ["home"],
+            "root": { "kind": "root", "children": [
+                { "kind": "folder", "name": "home", "children": [
+                    { "kind": "file", "name": "a.txt", "size": 1 },
+                    { "kind": "file", "name": "a.txt", "size": 1 }
+                ] }
+            ] }
+        }"#;
+
+        assert!(matches!(from_json(json), Err(PersistError::InvalidDocument(..))));
+    }
+
+    #[test]
+    fn load_rejects_invalid_names() {
+        for name in ["", "..", "a/b"] {
+            let json = format![r#"{{
+                "version": 1,
+                "current_dir": ["home"],
+                "root": {{ "kind": "root", "children": [
+                    {{ "kind": "folder", "name": "home", "children": [
+                        {{ "kind": "file", "name": "{}", "size": 1 }}
+                    ] }}
+                ] }}
+            }}"#, name];
+            assert!(matches!(from_json(&json), Err(PersistError::InvalidDocument(..))), "{}", name);
+        }
+    }
+
+    #[test]
+    fn load_rejects_unknown_version() {
+        let json = r#"{ "version": 99, "current_dir": ["home"], "root": { "kind": "root", "children": [] } }"#;
+        assert!(matches!(from_json(json), Err(PersistError::UnsupportedVersion(99))));
+    }
+
+    #[test]
+    fn load_rejects_missing_current_dir() {
+        let json = r#"{ "version": 1, "current_dir": ["home"], "root": { "kind": "root", "children": [] } }"#;
+        assert!(matches!(from_json(json), Err(PersistError::InvalidDocument(..))));
+    }
+}
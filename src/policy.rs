@@ -0,0 +1,317 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// The names of the presets that can be passed to `Policy::preset`.
+pub const PRESETS: [&str; 4] = ["default", "dos83", "posix", "permissive"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "length", rename_all = "snake_case")]
+/// Which extensions file names can have. The extension is the part of the name after its last
+/// dot, not counting a leading dot such as in `.bashrc`.
+pub enum ExtensionRule {
+    /// Any extension, or none at all.
+    Any,
+
+    /// An extension of exactly this many characters is required.
+    Exactly(usize),
+
+    /// No extension, or an extension of at most this many characters.
+    AtMost(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// Rules for the names of new files and folders.
+pub struct NamingPolicy {
+    /// The longest a name can be, in characters.
+    pub max_name_length: Option<usize>,
+
+    /// The longest the part of a file name before its extension can be, in characters.
+    pub max_stem_length: Option<usize>,
+
+    pub extension: ExtensionRule,
+    pub allow_spaces: bool,
+
+    /// Whether names can start with a dot, such as `.bashrc`.
+    pub allow_leading_dot: bool,
+}
+
+impl Default for NamingPolicy {
+    fn default() -> Self {
+        Self {
+            max_name_length: Some(12),
+            max_stem_length: None,
+            extension: ExtensionRule::Any,
+            allow_spaces: false,
+            allow_leading_dot: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// Limits on the sizes of files and of the whole tree.
+pub struct Limits {
+    /// The largest a file can be, in kilobytes.
+    pub max_file_size: Option<usize>,
+
+    /// Whether files can have a size of 0.
+    pub allow_empty_files: bool,
+
+    /// The total size of every file in the tree, in kilobytes. `None` is unlimited.
+    pub capacity: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_file_size: Some(4 * 1024 * 1024 - 1),
+            allow_empty_files: false,
+            capacity: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// The rules a tree follows, consulted by every command that creates or renames a node.
+/// Different trees can follow different rules to simulate different filesystems.
+pub struct Policy {
+    pub naming: NamingPolicy,
+    pub limits: Limits,
+}
+
+#[derive(Debug)]
+/// An error produced while loading a policy.
+pub enum PolicyError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    UnknownPreset(String),
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyError::Io(e) => write!(f, "{}", e),
+            PolicyError::Json(e) => write!(f, "Invalid JSON: {}", e),
+            PolicyError::UnknownPreset(name) => {
+                write!(f, "Unknown policy {} (expected one of {})", name, PRESETS.join(", "))
+            },
+        }
+    }
+}
+
+impl From<io::Error> for PolicyError {
+    fn from(e: io::Error) -> Self {
+        PolicyError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for PolicyError {
+    fn from(e: serde_json::Error) -> Self {
+        PolicyError::Json(e)
+    }
+}
+
+/// Split a file name into the part before its extension and the extension, if it has one.
+fn split_extension(name: &str) -> (&str, Option<&str>) {
+    let leading_dots = name.len() - name.trim_start_matches('.').len();
+
+    match name[leading_dots..].rsplit_once('.') {
+        Some((stem, extension)) => (&name[..leading_dots + stem.len()], Some(extension)),
+        None => (name, None),
+    }
+}
+
+impl NamingPolicy {
+    /// Check that a new file can be called `name`.
+    /// Returns the rule that was broken if it cannot.
+    pub fn check_file_name(&self, name: &str) -> Result<(), String> {
+        self.check_name(name, "file")?;
+
+        let (stem, extension) = split_extension(name);
+        if let Some(max) = self.max_stem_length.filter(|max| stem.chars().count() > *max) {
+            return Err(format!["the file name before the extension cannot be over {} characters", max]);
+        }
+
+        let extension_length = extension.map(|extension| extension.chars().count());
+        match self.extension {
+            ExtensionRule::Exactly(length) if extension_length != Some(length) => {
+                Err(format!["the file extension must be {} characters", length])
+            },
+            ExtensionRule::AtMost(length) if extension_length.is_some_and(|n| n > length) => {
+                Err(format!["the file extension cannot be over {} characters", length])
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that a new folder can be called `name`.
+    /// Returns the rule that was broken if it cannot.
+    pub fn check_folder_name(&self, name: &str) -> Result<(), String> {
+        self.check_name(name, "dir")
+    }
+
+    /// Check the rules shared by files and folders. `kind` is used in the error.
+    fn check_name(&self, name: &str, kind: &str) -> Result<(), String> {
+        if !self.allow_spaces && name.contains(' ') {
+            return Err(format!["the {} name cannot contain spaces", kind]);
+        }
+
+        if let Some(max) = self.max_name_length.filter(|max| name.chars().count() > *max) {
+            return Err(format!["the {} name cannot be over {} characters", kind, max]);
+        }
+
+        if !self.allow_leading_dot && name.starts_with('.') {
+            return Err(format!["the {} name cannot start with a dot", kind]);
+        }
+
+        Ok(())
+    }
+}
+
+impl Limits {
+    /// Check that a file can have the size `size` in kilobytes.
+    /// Returns the reason if it cannot.
+    pub fn check_file_size(&self, size: usize) -> Result<(), String> {
+        if size == 0 && !self.allow_empty_files {
+            return Err("cannot create a file with 0 size".to_string());
+        }
+
+        if let Some(max) = self.max_file_size.filter(|max| size > *max) {
+            return Err(format!["the file size can only be up to {}KB", max]);
+        }
+
+        Ok(())
+    }
+}
+
+impl Policy {
+    /// Get the preset called `name`, one of `PRESETS`.
+    pub fn preset(name: &str) -> Result<Self, PolicyError> {
+        match name {
+            "default" => Ok(Self::default()),
+            "dos83" => Ok(Self::dos83()),
+            "posix" => Ok(Self::posix()),
+            "permissive" => Ok(Self::permissive()),
+            _ => Err(PolicyError::UnknownPreset(name.to_string())),
+        }
+    }
+
+    /// Names with up to 8 characters and an optional extension of up to 3, as on FAT disks.
+    pub fn dos83() -> Self {
+        Self {
+            naming: NamingPolicy {
+                max_name_length: Some(12),
+                max_stem_length: Some(8),
+                extension: ExtensionRule::AtMost(3),
+                allow_spaces: false,
+                allow_leading_dot: false,
+            },
+            limits: Limits {
+                max_file_size: Some(4 * 1024 * 1024 - 1),
+                allow_empty_files: true,
+                capacity: None,
+            },
+        }
+    }
+
+    /// Names of up to 255 characters and files of any size, as on most unix filesystems.
+    pub fn posix() -> Self {
+        Self {
+            naming: NamingPolicy {
+                max_name_length: Some(255),
+                ..Self::permissive().naming
+            },
+            limits: Limits {
+                max_file_size: None,
+                allow_empty_files: true,
+                capacity: None,
+            },
+        }
+    }
+
+    /// No rules at all.
+    pub fn permissive() -> Self {
+        Self {
+            naming: NamingPolicy {
+                max_name_length: None,
+                max_stem_length: None,
+                extension: ExtensionRule::Any,
+                allow_spaces: true,
+                allow_leading_dot: true,
+            },
+            limits: Limits {
+                max_file_size: None,
+                allow_empty_files: true,
+                capacity: None,
+            },
+        }
+    }
+
+    /// Read a policy from a JSON string. Missing rules are taken from the default policy.
+    pub fn from_json(json: &str) -> Result<Self, PolicyError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load a policy from the JSON config file at `path`.
+    pub fn load_from_file(path: &Path) -> Result<Self, PolicyError> {
+        let json = fs::read_to_string(path)?;
+        Self::from_json(&json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_names() {
+        assert_eq!(split_extension("a.txt"), ("a", Some("txt")));
+        assert_eq!(split_extension("a.tar.gz"), ("a.tar", Some("gz")));
+        assert_eq!(split_extension("Makefile"), ("Makefile", None));
+        assert_eq!(split_extension(".bashrc"), (".bashrc", None));
+        assert_eq!(split_extension(".bashrc.old"), (".bashrc", Some("old")));
+    }
+
+    #[test]
+    fn presets() {
+        let dos83 = Policy::preset("dos83").unwrap().naming;
+        assert!(dos83.check_file_name("README.TXT").is_ok());
+        assert!(dos83.check_file_name("MAKEFILE").is_ok());
+        assert!(dos83.check_file_name("LONGNAME1.TXT").is_err());
+        assert!(dos83.check_file_name("A.HTML").is_err());
+        assert!(dos83.check_folder_name(".config").is_err());
+
+        let posix = Policy::preset("posix").unwrap();
+        assert!(posix.naming.check_file_name("my long file name.tar.gz").is_ok());
+        assert!(posix.limits.check_file_size(0).is_ok());
+
+        let default = Policy::default();
+        assert!(default.naming.check_folder_name("averylongname").is_err());
+        assert!(default.limits.check_file_size(0).is_err());
+        assert!(default.limits.check_file_size(4 * 1024 * 1024 - 1).is_ok());
+        assert!(default.limits.check_file_size(4 * 1024 * 1024).is_err());
+
+        assert!(matches!(Policy::preset("ntfs"), Err(PolicyError::UnknownPreset(..))));
+    }
+
+    #[test]
+    fn load_from_json() {
+        let json = r#"{ "naming": { "extension": { "kind": "exactly", "length": 3 } } }"#;
+        let policy = Policy::from_json(json).unwrap();
+
+        // rules that are not in the config keep their default.
+        assert_eq!(policy.naming.max_name_length, Some(12));
+        assert_eq!(policy.limits, Limits::default());
+        assert!(policy.naming.check_file_name("a.txt").is_ok());
+        assert!(policy.naming.check_file_name("Makefile").is_err());
+
+        let policy = Policy::posix();
+        assert_eq!(Policy::from_json(&serde_json::to_string(&policy).unwrap()).unwrap(), policy);
+    }
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::Context;
+use crate::tree::Node;
+
+#[derive(Debug, Clone)]
+/// A copy of the whole tree taken at a point in time.
+pub struct Snapshot {
+    root: Rc<Node>,
+
+    /// Names of the folders from the root to the current directory when the snapshot was taken.
+    current_dir: Vec<String>,
+}
+
+impl Snapshot {
+    /// Capture a copy of the tree held by `ctx`.
+    pub fn capture(ctx: &Context) -> Self {
+        Self {
+            root: ctx.root().deep_clone(),
+            current_dir: ctx.current_dir().path_names(),
+        }
+    }
+
+    /// Get the root of the copied tree.
+    pub fn root(&self) -> &Rc<Node> {
+        &self.root
+    }
+
+    /// Replace the tree held by `ctx` with a copy of this snapshot.
+    /// The snapshot is copied again so it can be restored more than once.
+    pub fn restore(&self, ctx: &Context) {
+        let root = self.root.deep_clone();
+        let current_dir = Node::find_folder(&root, &self.current_dir).unwrap();
+        ctx.replace_tree(Context::new(root, current_dir));
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// A difference between two trees. Paths are the full paths of the nodes.
+pub enum Change {
+    Added { path: String, is_folder: bool, size: usize },
+    Removed { path: String, is_folder: bool, size: usize },
+    Resized { path: String, is_folder: bool, from: usize, to: usize },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slash = |is_folder: bool| if is_folder { "/" } else { "" };
+
+        match self {
+            Change::Added { path, is_folder, size } => {
+                write!(f, "+ {}{} {}KB", path, slash(*is_folder), size)
+            },
+            Change::Removed { path, is_folder, size } => {
+                write!(f, "- {}{} {}KB", path, slash(*is_folder), size)
+            },
+            Change::Resized { path, is_folder, from, to } => {
+                write!(f, "~ {}{} {}KB -> {}KB", path, slash(*is_folder), from, to)
+            },
+        }
+    }
+}
+
+/// Add `node` and everything below it to `changes`, as either added or removed.
+fn add_subtree(node: &Node, added: bool, changes: &mut Vec<Change>) {
+    let path = node.to_string();
+    let is_folder = node.children().is_some();
+    let size = node.size().unwrap();
+
+    changes.push(if added {
+        Change::Added { path, is_folder, size }
+    } else {
+        Change::Removed { path, is_folder, size }
+    });
+
+    if let Some(children) = node.children() {
+        for child in children.borrow().iter() {
+            add_subtree(child, added, changes);
+        }
+    }
+}
+
+/// Compare the children of `before` and `after`, which are at the same path in two trees.
+/// A file and a folder with the same name are treated as different nodes.
+fn diff_children(before: &Node, after: &Node, changes: &mut Vec<Change>) {
+    let before_children = before.children().unwrap().borrow();
+    let after_children = after.children().unwrap().borrow();
+
+    for old in before_children.iter() {
+        let new = after_children.iter().find(|new| {
+            new.name() == old.name() && new.children().is_some() == old.children().is_some()
+        });
+
+        let Some(new) = new else {
+            add_subtree(old, false, changes);
+            continue;
+        };
+
+        if old.size() != new.size() {
+            changes.push(Change::Resized {
+                path: new.to_string(),
+                is_folder: new.children().is_some(),
+                from: old.size().unwrap(),
+                to: new.size().unwrap(),
+            });
+        }
+
+        if new.children().is_some() {
+            diff_children(old, new, changes);
+        }
+    }
+
+    for new in after_children.iter() {
+        let exists = before_children.iter().any(|old| {
+            new.name() == old.name() && new.children().is_some() == old.children().is_some()
+        });
+
+        if !exists {
+            add_subtree(new, true, changes);
+        }
+    }
+}
+
+/// List the files and folders that were added, removed or resized between two trees.
+pub fn diff(before: &Rc<Node>, after: &Rc<Node>) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_children(before, after, &mut changes);
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::NodePathSegment;
+    use crate::tree::build_tree;
+
+    #[test]
+    fn restore_snapshot() {
+        let ctx = build_tree("test_user");
+        let snapshot = Snapshot::capture(&ctx);
+
+        let user = ctx.current_dir();
+        ctx.remove_node(user, "music").unwrap();
+
+        snapshot.restore(&ctx);
+        assert!(ctx.node_from_path(&vec![NodePathSegment::Name("music".to_string())]).is_ok());
+        assert_eq!(ctx.current_dir().to_string(), "/home/test_user");
+
+        // changes after restoring do not change the snapshot.
+        let user = ctx.current_dir();
+        ctx.remove_node(user, "music").unwrap();
+        assert!(snapshot.root().child("home").unwrap().child("test_user").unwrap().child("music").is_some());
+    }
+
+    #[test]
+    fn diff_snapshots() {
+        let ctx = build_tree("test_user");
+        let before = Snapshot::capture(&ctx);
+
+        let user = ctx.current_dir();
+        let music = user.child("music").unwrap();
+        ctx.remove_node(Rc::clone(&music), "1.mp3").unwrap();
+        ctx.add_node(Rc::clone(&user), Rc::new(Node::new_file("a.txt", 4))).unwrap();
+        let after = Snapshot::capture(&ctx);
+
+        let changes = diff(before.root(), after.root());
+        assert_eq!(changes, vec![
+            Change::Resized { path: "/home".to_string(), is_folder: true, from: 12, to: 15 },
+            Change::Resized { path: "/home/test_user".to_string(), is_folder: true, from: 12, to: 15 },
+            Change::Resized { path: "/home/test_user/music".to_string(), is_folder: true, from: 10, to: 9 },
+            Change::Removed { path: "/home/test_user/music/1.mp3".to_string(), is_folder: false, size: 1 },
+            Change::Added { path: "/home/test_user/a.txt".to_string(), is_folder: false, size: 4 },
+        ]);
+    }
+}
@@ -1,8 +1,13 @@
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::rc::{Rc, Weak};
+use std::time::SystemTime;
 use std::fmt;
 
+use crate::history::{History, Location, Operation};
 use crate::parser::{NodePath, NodePathSegment};
+use crate::policy::Policy;
+use crate::snapshot::Snapshot;
 
 
 #[derive(Debug, Clone)]
@@ -11,6 +16,28 @@ use crate::parser::{NodePath, NodePathSegment};
 pub struct Context {
     root: RefCell<Rc<Node>>,
     current_dir: RefCell<Rc<Node>>,
+
+    /// Operations that can be undone and redone.
+    history: RefCell<History>,
+
+    /// Named copies of the tree.
+    snapshots: RefCell<BTreeMap<String, Snapshot>>,
+
+    /// The naming rules and size limits of the tree.
+    policy: RefCell<Policy>,
+
+    /// Asks the user to confirm changes such as those made by `rm -i`.
+    prompt: RefCell<Option<Prompt>>,
+}
+
+#[derive(Clone)]
+/// Asks the user a yes or no question and returns whether they answered yes.
+struct Prompt(Rc<dyn Fn(&str) -> bool>);
+
+impl fmt::Debug for Prompt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Prompt")
+    }
 }
 
 impl Context {
@@ -19,12 +46,240 @@ impl Context {
         Self {
             root: RefCell::new(root),
             current_dir: RefCell::new(current_dir),
+            history: RefCell::new(History::default()),
+            snapshots: RefCell::new(BTreeMap::new()),
+            policy: RefCell::new(Policy::default()),
+            prompt: RefCell::new(None),
+        }
+    }
+
+    /// Get the root of the tree.
+    pub fn root(&self) -> Rc<Node> {
+        Rc::clone(&self.root.borrow())
+    }
+
+    /// Replace the tree held by this context with the tree held by `other`.
+    /// The history is cleared as it refers to nodes in the old tree.
+    pub fn replace_tree(&self, other: Context) {
+        *self.root.borrow_mut() = other.root.into_inner();
+        *self.current_dir.borrow_mut() = other.current_dir.into_inner();
+        self.history.borrow_mut().clear();
+    }
+
+    /// Add `child` to `parent` and record the operation so it can be undone.
+    /// Fails if `parent` already has a child with the same name, or if the tree does not have
+    /// space for it.
+    pub fn add_node(&self, parent: Rc<Node>, child: Rc<Node>) -> Result<(), NodeError> {
+        self.check_space(child.size().unwrap_or(0))?;
+        Rc::clone(&parent).add(Rc::clone(&child))?;
+
+        let index = parent.children().unwrap().borrow().len() - 1;
+        self.history.borrow_mut().record(Operation::Insert { parent, index, node: child });
+        Ok(())
+    }
+
+    /// Remove the child named `name` from `parent` and record the operation so it can be undone.
+    /// The removed node keeps its children so the whole subtree can be restored.
+    pub fn remove_node(&self, parent: Rc<Node>, name: &str) -> Result<Rc<Node>, String> {
+        let index = parent.index_of(name)
+            .ok_or(format!["Could not locate item: {}", name])?;
+        let node = Rc::clone(&parent).remove_at(index);
+
+        self.history.borrow_mut().record(Operation::Remove { parent, index, node: Rc::clone(&node) });
+        Ok(node)
+    }
+
+    /// Change the size of the file `node` and record the operation so it can be undone.
+    pub fn resize_node(&self, node: Rc<Node>, new_size: usize) -> Result<(), NodeError> {
+        let from = node.size().ok_or(NodeError::WrongType)?;
+        self.check_space(new_size.saturating_sub(from))?;
+        Rc::clone(&node).resize(new_size)?;
+
+        self.history.borrow_mut().record(Operation::Resize { node, from, to: new_size });
+        Ok(())
+    }
+
+    /// Move `node` into `new_parent` with the name `new_name` and record the operation so it
+    /// can be undone. A node renamed within the same folder keeps its position.
+    pub fn move_node(&self, node: Rc<Node>, new_parent: Rc<Node>, new_name: &str) -> Result<(), NodeError> {
+        let old_parent = node.parent_node().ok_or(NodeError::WrongType)?;
+        if new_parent.children().is_none() || node.is_ancestor_of(&new_parent) {
+            return Err(NodeError::WrongType);
+        }
+
+        // check for a clash before the node is taken out, so a failed move changes nothing.
+        if new_parent.child(new_name).is_some_and(|existing| !Rc::ptr_eq(&existing, &node)) {
+            return Err(NodeError::AlreadyExists(new_name.to_string()));
+        }
+
+        let old_index = old_parent.children().unwrap().borrow().iter()
+            .position(|child| Rc::ptr_eq(child, &node))
+            .unwrap();
+        let from = Location {
+            parent: old_parent,
+            index: old_index,
+            name: node.name().unwrap(),
+        };
+        Rc::clone(&from.parent).remove_at(from.index);
+
+        let to = Location {
+            index: if Rc::ptr_eq(&from.parent, &new_parent) {
+                from.index
+            } else {
+                new_parent.children().unwrap().borrow().len()
+            },
+            parent: new_parent,
+            name: new_name.to_string(),
+        };
+        node.set_name(&to.name)?;
+        Rc::clone(&to.parent).insert(to.index, Rc::clone(&node))?;
+
+        self.history.borrow_mut().record(Operation::Move { node, from, to });
+        Ok(())
+    }
+
+    /// Start grouping the operations recorded in the history, so that everything changed until
+    /// `commit_change` is undone and redone at once. Commands are grouped when they run.
+    pub fn begin_change(&self) {
+        self.history.borrow_mut().begin();
+    }
+
+    /// Finish the change started with `begin_change`.
+    pub fn commit_change(&self) {
+        self.history.borrow_mut().commit();
+    }
+
+    /// Undo the most recent change.
+    /// Returns false if there is nothing to undo.
+    pub fn undo(&self) -> bool {
+        let operations = self.history.borrow_mut().undo();
+        match operations {
+            Some(operations) => {
+                self.leave_detached_dir(&operations);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Redo the most recently undone change.
+    /// Returns false if there is nothing to redo.
+    pub fn redo(&self) -> bool {
+        let operations = self.history.borrow_mut().redo();
+        match operations {
+            Some(operations) => {
+                self.leave_detached_dir(&operations);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Set how many changes are kept in the history.
+    pub fn set_history_depth(&self, depth: usize) {
+        self.history.borrow_mut().set_depth(depth);
+    }
+
+    /// If undoing or redoing `operations` removed the current directory from the tree,
+    /// change the current directory to the first folder they changed that is still in it.
+    fn leave_detached_dir(&self, operations: &[Operation]) {
+        if Self::is_attached(&self.current_dir.borrow()) {
+            return;
+        }
+
+        let folder = operations.iter()
+            .filter_map(|operation| operation.parent())
+            .find(Self::is_attached);
+        if let Some(folder) = folder {
+            self.set_current_dir(folder);
+        }
+    }
+
+    /// Check if `node` is still in the tree, by following its parents up to the root.
+    fn is_attached(node: &Rc<Node>) -> bool {
+        let mut node = Rc::clone(node);
+        while let Some(parent) = node.parent() {
+            let Some(parent) = parent.borrow().upgrade() else {
+                return false;
+            };
+            node = parent;
+        }
+        true
+    }
+
+    /// Set whether names that only differ by case are treated as the same name.
+    /// Existing names are not changed when this is enabled.
+    pub fn set_case_insensitive(&self, enabled: bool) {
+        self.root.borrow().set_case_insensitive(enabled);
+    }
+
+    /// Set the rules that new and renamed nodes must follow.
+    /// Existing nodes are not checked against the new rules.
+    pub fn set_policy(&self, policy: Policy) {
+        *self.policy.borrow_mut() = policy;
+    }
+
+    /// Get the rules that new and renamed nodes must follow.
+    pub fn policy(&self) -> Policy {
+        self.policy.borrow().clone()
+    }
+
+    /// Get the total size of every file in the tree, in kilobytes.
+    pub fn used_space(&self) -> usize {
+        self.root().child_nodes().iter().map(|child| child.size().unwrap()).sum()
+    }
+
+    /// Get the total size the files in the tree can have, in kilobytes. `None` is unlimited.
+    pub fn capacity(&self) -> Option<usize> {
+        self.policy.borrow().limits.capacity
+    }
+
+    /// Set the total size the files in the tree can have. Files already in the tree are kept
+    /// even if they use more than `capacity`.
+    pub fn set_capacity(&self, capacity: Option<usize>) {
+        self.policy.borrow_mut().limits.capacity = capacity;
+    }
+
+    /// Check that `size` more kilobytes fit in the tree.
+    fn check_space(&self, size: usize) -> Result<(), NodeError> {
+        match self.capacity() {
+            Some(capacity) if size > 0 && self.used_space() + size > capacity => Err(NodeError::NoSpace),
+            _ => Ok(()),
         }
     }
 
+    /// Save `snapshot` under `name`, replacing any snapshot with the same name.
+    pub fn add_snapshot(&self, name: &str, snapshot: Snapshot) {
+        self.snapshots.borrow_mut().insert(name.to_string(), snapshot);
+    }
+
+    /// Get the snapshot saved under `name`.
+    pub fn snapshot(&self, name: &str) -> Option<Snapshot> {
+        self.snapshots.borrow().get(name).cloned()
+    }
+
+    /// Get the names and snapshots of every saved snapshot, ordered by name.
+    pub fn snapshots(&self) -> Vec<(String, Snapshot)> {
+        self.snapshots.borrow().iter()
+            .map(|(name, snapshot)| (name.clone(), snapshot.clone()))
+            .collect()
+    }
+
+    /// Set how the user is asked to confirm changes, such as with `rm -i`.
+    pub fn set_prompt(&self, prompt: impl Fn(&str) -> bool + 'static) {
+        *self.prompt.borrow_mut() = Some(Prompt(Rc::new(prompt)));
+    }
+
+    /// Ask the user a yes or no question. Without a prompt every question is answered no,
+    /// so nothing that needs confirming is changed.
+    pub fn confirm(&self, question: &str) -> bool {
+        let prompt = self.prompt.borrow().clone();
+        prompt.is_some_and(|Prompt(prompt)| prompt(question))
+    }
+
     /// Get the current directory.
-    pub fn current_dir(&self) -> &RefCell<Rc<Node>> {
-        &self.current_dir
+    pub fn current_dir(&self) -> Rc<Node> {
+        Rc::clone(&self.current_dir.borrow())
     }
 
     /// Set the curent directory to `new_dir`
@@ -35,30 +290,23 @@ impl Context {
     /// Change a directory to one of its children.
     /// with the name: `dir_name`
     fn dir_to_child(current_dir: &mut Rc<Node>, dir_name: &str) -> Result<(), InvalidFolder> {
-        let children = current_dir.children().unwrap().borrow();
-        let dir = children.iter().find(|dir| { 
-            &dir.name().unwrap() == dir_name
-        });
+        let dir = current_dir.child(dir_name).filter(|dir| dir.children().is_some());
 
         if let Some(dir) = dir {
-            let dir = Rc::clone(&dir);
-
-            // the reference to self.current_dir's children means that current_dir is
-            // borrowed. Therefore children must be dropped before borrowing current_dir mutably
-            // to assign to it.
-            drop(children);
-
             *current_dir = dir;
             return Ok(());
         }
 
-        Err(InvalidFolder)
+        Err(InvalidFolder(dir_name.to_string()))
     }
 
-    /// Change `dir` to its parent
-    fn dir_to_parent(dir: &mut Rc<Node>) {
-        let parent = Weak::upgrade(&dir.parent().unwrap().borrow()).unwrap();
+    /// Change `dir` to its parent.
+    /// Fails if `dir` is the root as it has no parent.
+    fn dir_to_parent(dir: &mut Rc<Node>) -> Result<(), InvalidFolder> {
+        let parent = dir.parent_node().ok_or(InvalidFolder("..".to_string()))?;
+
         *dir = parent;
+        Ok(())
     }
 
     /// Replaces the directory stored in the dir parameter with the root directory 
@@ -67,26 +315,60 @@ impl Context {
     }
 
     /// Get a node from the tree from a `NodePath`.
+    /// Every segment of the path must be a folder.
     pub fn node_from_path(&self, dir: &NodePath) -> Result<Rc<Node>, InvalidFolder> {
         let mut buffer_dir = Rc::clone(&self.current_dir.borrow());
 
         for path_segment in dir.iter() {
             match path_segment {
                 NodePathSegment::Root => self.dir_to_root(&mut buffer_dir),
-                NodePathSegment::Dir(folder_name) => {
+                NodePathSegment::Name(folder_name) => {
                     Self::dir_to_child(&mut buffer_dir, folder_name)?;
                 },
-                NodePathSegment::Parent => Self::dir_to_parent(&mut buffer_dir),
-                _ => return Err(InvalidFolder)
+                NodePathSegment::Parent => Self::dir_to_parent(&mut buffer_dir)?,
+                // globs are expanded before a path is followed.
+                NodePathSegment::Pattern(pattern) => return Err(InvalidFolder(pattern.clone())),
             }
         }
 
         Ok(buffer_dir)
     }
+
+    /// Get a node from the tree from a `NodePath`.
+    /// Unlike `node_from_path`, the last segment of the path may be a file.
+    pub fn find_node(&self, path: &NodePath) -> Result<Rc<Node>, InvalidFolder> {
+        match path.last() {
+            Some(NodePathSegment::Name(name)) => {
+                let parent = self.node_from_path(&path[..path.len() - 1].to_vec())?;
+                parent.child(name).ok_or(InvalidFolder(name.clone()))
+            },
+            _ => self.node_from_path(path),
+        }
+    }
+
+    /// Check that the size of every folder matches the total size of the files below it.
+    /// Returns every folder with a mismatched size.
+    pub fn verify_sizes(&self) -> Result<(), Vec<SizeMismatch>> {
+        let mut mismatches = Vec::new();
+        self.root.borrow().calculate_size(&mut mismatches);
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
-pub struct InvalidFolder;
+/// A path could not be followed. Holds the name of the segment that was not found.
+pub struct InvalidFolder(pub String);
+
+impl fmt::Display for InvalidFolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Could not locate folder: {}", self.0)
+    }
+}
 
 #[derive(Debug)]
 /// Represents a node in the file tree, could be the root, a folder, or a file.
@@ -94,6 +376,9 @@ pub enum Node {
     /// Root is not accessable by the user, but it only contains children.
     Root {
         children: RefCell<Vec<Rc<Node>>>,
+
+        /// Whether names that only differ by case are treated as the same name in this tree.
+        case_insensitive: RefCell<bool>,
     },
     /// A folder with a parent and children containing more nodes.
     Folder {
@@ -108,6 +393,9 @@ pub enum Node {
 
         /// Depth represents the depth into the heirarchy where the root has a depth of 0
         depth: RefCell<usize>,
+
+        /// When the folder or its list of children was last changed
+        modified: RefCell<SystemTime>,
     },
     /// A file has no children
     File {
@@ -121,14 +409,20 @@ pub enum Node {
 
         /// Depth represents the depth into the heirarchy where the root has a depth of 0
         depth: RefCell<usize>,
+
+        /// When the file was last changed
+        modified: RefCell<SystemTime>,
     },
 }
 
 impl fmt::Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(parent) = self.parent() {
-            let parent = parent.borrow().upgrade().unwrap();
-            return write!(f, "{}/{}", parent, self.name().unwrap_or(String::new()));
+        if let Some(parent) = self.parent_node() {
+            return write!(f, "{}/{}", parent, self.name().unwrap_or_default());
+        }
+        if let Some(name) = self.name() {
+            // the node has been detached from the tree.
+            return write!(f, "/{}", name);
         }
         write!(f, "")
     }
@@ -139,6 +433,7 @@ impl Node {
     pub fn new_root() -> Self {
         Self::Root {
             children: RefCell::new(Vec::new()),
+            case_insensitive: RefCell::new(false),
         }
     }
 
@@ -150,6 +445,7 @@ impl Node {
             parent: RefCell::new(Weak::new()),
             children: RefCell::new(Vec::new()),
             depth: RefCell::new(0),
+            modified: RefCell::new(SystemTime::now()),
         }
     }
 
@@ -160,27 +456,38 @@ impl Node {
             size: RefCell::new(size),
             parent: RefCell::new(Weak::new()),
             depth: RefCell::new(0),
+            modified: RefCell::new(SystemTime::now()),
         }
     }
 
     /// Get a reference to the node's parent, if it has one
     pub fn parent(&self) -> Option<&RefCell<Weak<Node>>> {
-        match &*self {
+        match self {
             Node::Folder { parent, .. } => Some(parent),
             Node::File { parent, .. } => Some(parent),
             Node::Root { .. } => None,
         }
     }
 
+    /// Get the node's parent, if it has one and it is still in the tree.
+    pub fn parent_node(&self) -> Option<Rc<Node>> {
+        self.parent()?.borrow().upgrade()
+    }
+
     /// Get a reference to the node's children, if it has them
     pub fn children(&self) -> Option<&RefCell<Vec<Rc<Node>>>> {
-        match &*self {
+        match self {
             Node::Folder { children, .. } => Some(children),
             Node::Root { children, .. } => Some(children),
             Node::File { .. } => None,
         }
     }
 
+    /// Get a list of the node's children. Files have no children.
+    pub fn child_nodes(&self) -> Vec<Rc<Node>> {
+        self.children().map(|children| children.borrow().clone()).unwrap_or_default()
+    }
+
     /// Get the node's size
     pub fn size(&self) -> Option<usize> {
         match self {
@@ -202,8 +509,8 @@ impl Node {
     /// Get the depth of the node
     pub fn depth(&self) -> usize {
         match self {
-            Node::Folder { depth, .. } => depth.borrow().clone(),
-            Node::File { depth, .. } => depth.borrow().clone(),
+            Node::Folder { depth, .. } => *depth.borrow(),
+            Node::File { depth, .. } => *depth.borrow(),
             Node::Root { .. } => 0,
         }
     }
@@ -217,50 +524,360 @@ impl Node {
         }
     }
 
-    /// Add the node: `child` to this node.
-    pub fn add(self: Rc<Self>, child: Rc<Self>) -> Result<(), NodeTypeError> {
-        let add_child_to_children =
-            |children: &RefCell<Vec<Rc<Self>>>,
-            file_size: Option<&RefCell<usize>>| -> Result<(), NodeTypeError> {
-                *child.parent().ok_or(NodeTypeError)?.borrow_mut() = Rc::downgrade(&self);
-                *child.depth_ref().ok_or(NodeTypeError)?.borrow_mut() = self.depth() + 1;
-                children.borrow_mut().push(Rc::clone(&child));
-
-                if let Some(size) = file_size {
-                    *size.borrow_mut() += child.size().unwrap();
-                }
+    /// Set whether names ignore case. This only has an effect on the root.
+    pub fn set_case_insensitive(&self, enabled: bool) {
+        if let Node::Root { case_insensitive, .. } = self {
+            *case_insensitive.borrow_mut() = enabled;
+        }
+    }
+
+    /// Get the time the node was last changed. The root is never changed.
+    pub fn modified(&self) -> Option<SystemTime> {
+        match self {
+            Node::Folder { modified, .. } | Node::File { modified, .. } => Some(*modified.borrow()),
+            Node::Root { .. } => None,
+        }
+    }
 
+    /// Set the time the node was last changed to now.
+    pub fn touch(&self) {
+        if let Node::Folder { modified, .. } | Node::File { modified, .. } = self {
+            *modified.borrow_mut() = SystemTime::now();
+        }
+    }
+
+    /// Check if names in the tree containing this node ignore case.
+    /// Nodes that are not attached to a root are case sensitive.
+    pub fn is_case_insensitive(&self) -> bool {
+        let mut parent = self.parent_node();
+        if let Node::Root { case_insensitive, .. } = self {
+            return *case_insensitive.borrow();
+        }
+
+        while let Some(node) = parent {
+            if let Node::Root { case_insensitive, .. } = &*node {
+                return *case_insensitive.borrow();
+            }
+            parent = node.parent_node();
+        }
+
+        false
+    }
+
+    /// Check if `name` and `other` name the same child of this node.
+    fn same_name(&self, name: &str, other: &str) -> bool {
+        if self.is_case_insensitive() {
+            name.to_lowercase() == other.to_lowercase()
+        } else {
+            name == other
+        }
+    }
+
+    /// Get the child of this node with the name `name`.
+    pub fn child(&self, name: &str) -> Option<Rc<Node>> {
+        self.children()?.borrow().iter()
+            .find(|child| self.same_name(&child.name().unwrap(), name))
+            .cloned()
+    }
+
+    /// Change the name of the node. The root has no name so it cannot be renamed.
+    pub fn set_name(&self, new_name: &str) -> Result<(), NodeError> {
+        match self {
+            Node::Folder { name, .. } | Node::File { name, .. } => {
+                *name.borrow_mut() = new_name.to_string();
                 Ok(())
-            };
+            },
+            Node::Root { .. } => Err(NodeError::WrongType),
+        }
+    }
+
+    /// Check if `other` is this node or is anywhere below it.
+    pub fn is_ancestor_of(self: &Rc<Self>, other: &Rc<Node>) -> bool {
+        let mut current = Some(Rc::clone(other));
+
+        while let Some(node) = current {
+            if Rc::ptr_eq(self, &node) {
+                return true;
+            }
+            current = node.parent_node();
+        }
 
-        match &*self {
-            Node::Root { children, .. } => add_child_to_children(children, None)?,
-            Node::Folder { children, size, .. } => add_child_to_children(children, Some(size))?,
-            Node::File { .. } => return Err(NodeTypeError),
+        false
+    }
+
+    /// Get the names of the nodes from the top of the tree down to this node.
+    pub fn path_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        names.extend(self.name());
+
+        let mut parent = self.parent_node();
+        while let Some(node) = parent {
+            names.extend(node.name());
+            parent = node.parent_node();
+        }
+
+        names.reverse();
+        names
+    }
+
+    /// Follow `names` down from `start`, returning the folder that is reached.
+    pub fn find_folder(start: &Rc<Node>, names: &[String]) -> Option<Rc<Node>> {
+        let mut folder = Rc::clone(start);
+        for name in names {
+            folder = folder.child(name).filter(|child| child.children().is_some())?;
+        }
+
+        Some(folder)
+    }
+
+    /// Create a copy of this node and everything below it.
+    /// The copy has its own parent links and depths and is not attached to a parent.
+    pub fn deep_clone(&self) -> Rc<Node> {
+        let copy = Rc::new(match self {
+            Node::Root { case_insensitive, .. } => {
+                let root = Node::new_root();
+                root.set_case_insensitive(*case_insensitive.borrow());
+                root
+            },
+            Node::Folder { name, .. } => Node::new_folder(&name.borrow()),
+            Node::File { name, size, .. } => Node::new_file(&name.borrow(), *size.borrow()),
+        });
+
+        if let Some(children) = self.children() {
+            for child in children.borrow().iter() {
+                Rc::clone(&copy).add(child.deep_clone()).unwrap();
+            }
+        }
+
+        // adding the children changes the time, so the time is copied afterwards.
+        if let (Some(time), Node::Folder { modified, .. } | Node::File { modified, .. }) = (self.modified(), &*copy) {
+            *modified.borrow_mut() = time;
+        }
+
+        copy
+    }
+
+    /// Set the depth of the node to `new_depth` and update the depths of all its descendants.
+    fn set_depth(&self, new_depth: usize) {
+        if let Some(depth) = self.depth_ref() {
+            *depth.borrow_mut() = new_depth;
+        }
+
+        if let Some(children) = self.children() {
+            for child in children.borrow().iter() {
+                child.set_depth(new_depth + 1);
+            }
+        }
+    }
+
+    /// Apply a change in size of `delta` kilobytes to `start` and every folder above it.
+    /// The root has no size so the walk ends once it is reached.
+    fn propagate_size(start: Rc<Self>, delta: isize) {
+        let mut current = Some(start);
+
+        while let Some(node) = current {
+            match &*node {
+                Node::Folder { size, .. } => {
+                    let new_size = size.borrow().saturating_add_signed(delta);
+                    *size.borrow_mut() = new_size;
+                },
+                Node::File { .. } | Node::Root { .. } => (),
+            }
+
+            current = node.parent_node();
+        }
+    }
+
+    /// Add the node: `child` to this node.
+    /// The size of the child is added to this node and all of its ancestors.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(self: Rc<Self>, child: Rc<Self>) -> Result<(), NodeError> {
+        let index = self.children().ok_or(NodeError::WrongType)?.borrow().len();
+        self.insert(index, child)
+    }
+
+    /// Add the node: `child` to this node at position `index` in its children.
+    /// The size of the child is added to this node and all of its ancestors.
+    /// Fails if this node already has a child with the same name.
+    pub fn insert(self: Rc<Self>, index: usize, child: Rc<Self>) -> Result<(), NodeError> {
+        let children = match &*self {
+            Node::Root { children, .. } | Node::Folder { children, .. } => children,
+            Node::File { .. } => return Err(NodeError::WrongType),
+        };
+
+        let name = child.name().ok_or(NodeError::WrongType)?;
+        if self.index_of(&name).is_some() {
+            return Err(NodeError::AlreadyExists(name));
         }
 
+        self.touch();
+        *child.parent().ok_or(NodeError::WrongType)?.borrow_mut() = Rc::downgrade(&self);
+        child.set_depth(self.depth() + 1);
+        children.borrow_mut().insert(index, Rc::clone(&child));
+
+        Self::propagate_size(self, child.size().unwrap() as isize);
+
         Ok(())
     }
 
     /// Remove a node by name from this node.
-    pub fn remove(self: Rc<Self>, node_name: &str) -> Result<(), String> {
-        let get_index = || {
-            for (i, node) in self.children().unwrap().borrow().iter().enumerate() {
-                if &node.name().unwrap() == node_name {
-                    return Ok(i);
-                }
-            }
-            return Err(format!["Could not locate item: {}", node_name]);
+    /// The size of the removed node is taken off this node and all of its ancestors,
+    /// and the removed node is returned.
+    pub fn remove(self: Rc<Self>, node_name: &str) -> Result<Rc<Node>, String> {
+        let index = self.index_of(node_name)
+            .ok_or(format!["Could not locate item: {}", node_name])?;
+
+        Ok(self.remove_at(index))
+    }
+
+    /// Get the position of the child named `node_name` in this node's children.
+    pub fn index_of(&self, node_name: &str) -> Option<usize> {
+        self.children()?.borrow().iter()
+            .position(|node| self.same_name(&node.name().unwrap(), node_name))
+    }
+
+    /// Remove the child at `index` from this node's children and update the sizes of the
+    /// ancestors. The order of the remaining children is kept.
+    pub fn remove_at(self: Rc<Self>, index: usize) -> Rc<Node> {
+        let removed = self.children().unwrap().borrow_mut().remove(index);
+        self.touch();
+
+        // the removed node is no longer part of the tree.
+        *removed.parent().unwrap().borrow_mut() = Weak::new();
+        Self::propagate_size(self, -(removed.size().unwrap() as isize));
+
+        removed
+    }
+
+    /// Remove this node from its parent, if it has one.
+    pub fn detach(self: &Rc<Self>) {
+        let Some(parent) = self.parent_node() else {
+            return;
         };
 
-        let index = get_index()?;
-        self.children().unwrap().borrow_mut().swap_remove(index);
+        let index = parent.children().unwrap().borrow().iter()
+            .position(|node| Rc::ptr_eq(node, self));
+
+        if let Some(index) = index {
+            parent.remove_at(index);
+        }
+    }
+
+    /// Change the size of a file to `new_size`.
+    /// The difference in size is applied to every folder above the file.
+    pub fn resize(self: Rc<Self>, new_size: usize) -> Result<(), NodeError> {
+        let Node::File { size, parent, .. } = &*self else {
+            return Err(NodeError::WrongType);
+        };
+
+        let delta = new_size as isize - *size.borrow() as isize;
+        *size.borrow_mut() = new_size;
+        self.touch();
+
+        if let Some(parent) = parent.borrow().upgrade() {
+            Self::propagate_size(parent, delta);
+        }
+
         Ok(())
     }
+
+    /// Move this node from its current parent into `new_parent`.
+    /// Sizes are taken off the old ancestors and added to the new ones.
+    pub fn move_to(self: Rc<Self>, new_parent: Rc<Self>) -> Result<(), NodeError> {
+        if new_parent.children().is_none() {
+            return Err(NodeError::WrongType);
+        }
+
+        if self.parent().is_none() || self.is_ancestor_of(&new_parent) {
+            return Err(NodeError::WrongType);
+        }
+
+        self.detach();
+        new_parent.add(self)
+    }
+
+    /// Recalculate the size of this node from the files below it.
+    /// Any folder whose recorded size differs from the calculated size is added to `mismatches`.
+    fn calculate_size(&self, mismatches: &mut Vec<SizeMismatch>) -> usize {
+        match self {
+            Node::File { size, .. } => *size.borrow(),
+            Node::Folder { size, children, .. } => {
+                let calculated = children.borrow().iter()
+                    .map(|child| child.calculate_size(mismatches))
+                    .sum();
+
+                if *size.borrow() != calculated {
+                    mismatches.push(SizeMismatch {
+                        path: self.to_string(),
+                        recorded: *size.borrow(),
+                        calculated,
+                    });
+                }
+
+                calculated
+            },
+            Node::Root { children, .. } => {
+                children.borrow().iter()
+                    .map(|child| child.calculate_size(mismatches))
+                    .sum()
+            },
+        }
+    }
 }
 
-#[derive(Debug)]
-pub struct NodeTypeError;
+#[derive(Debug, PartialEq)]
+/// A folder whose recorded size does not match the total size of the files below it.
+pub struct SizeMismatch {
+    /// Full path of the folder.
+    pub path: String,
+
+    /// Size stored on the folder (in kilobytes).
+    pub recorded: usize,
+
+    /// Size calculated from the files below the folder (in kilobytes).
+    pub calculated: usize,
+}
+
+#[derive(Debug, PartialEq)]
+/// An error produced when changing the tree.
+pub enum NodeError {
+    /// The node is the wrong kind for the operation, such as adding a child to a file.
+    WrongType,
+
+    /// The folder already has a child with this name.
+    AlreadyExists(String),
+
+    /// The change would make the files in the tree larger than its capacity.
+    NoSpace,
+}
+
+impl fmt::Display for NodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeError::WrongType => write!(f, "The item is the wrong type for this operation"),
+            NodeError::AlreadyExists(name) => write!(f, "An item named {} already exists", name),
+            NodeError::NoSpace => write!(f, "No space left in the tree"),
+        }
+    }
+}
+
+/// Check that `name` can be the name of a file or folder in any tree.
+/// Returns the reason if it cannot, as paths would not be able to refer to the node.
+pub fn check_node_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("names cannot be empty".to_string());
+    }
+
+    if name == "." || name == ".." {
+        return Err(format!["{} cannot be used as a name", name]);
+    }
+
+    if name.contains('/') {
+        return Err(format!["{} cannot contain a /", name]);
+    }
+
+    Ok(())
+}
 
 /// Build a hardcoded file tree
 pub fn build_tree(username: &str) -> Context {
@@ -317,7 +934,7 @@ mod tests {
         assert_eq!(folder.size().unwrap(), 0);
 
         let file = Rc::new(Node::new_file("file", 2));
-        let other_file = Rc::new(Node::new_file("file", 3));
+        let other_file = Rc::new(Node::new_file("other_file", 3));
 
         Rc::clone(&folder).add(file).unwrap();
         Rc::clone(&folder).add(other_file).unwrap();
@@ -328,15 +945,130 @@ mod tests {
     #[test]
     fn navigate_valid() {
         let ctx = build_tree("test_user");
-        let result = ctx.node_from_path(&vec![NodePathSegment::Dir("documents".to_string())]);
+        let result = ctx.node_from_path(&vec![NodePathSegment::Name("documents".to_string())]);
         assert!(result.is_ok());
     }
     
     #[test]
     fn navigate_invalid() {
         let ctx = build_tree("test_user");
-        let result = ctx.node_from_path(&vec![NodePathSegment::Dir("abcdefg".to_string())]);
+        let result = ctx.node_from_path(&vec![NodePathSegment::Name("abcdefg".to_string())]);
         assert!(result.is_err());
     }
-}
 
+    #[test]
+    fn size_propagates_to_ancestors() {
+        let ctx = build_tree("test_user");
+        let home = ctx.node_from_path(&vec![NodePathSegment::Root, NodePathSegment::Name("home".to_string())]).unwrap();
+        let music = ctx.node_from_path(&vec![NodePathSegment::Name("music".to_string())]).unwrap();
+        assert_eq!(home.size().unwrap(), 12);
+
+        Rc::clone(&music).add(Rc::new(Node::new_file("11.mp3", 5))).unwrap();
+        assert_eq!(music.size().unwrap(), 15);
+        assert_eq!(home.size().unwrap(), 17);
+
+        Rc::clone(&music).remove("1.mp3").unwrap();
+        assert_eq!(music.size().unwrap(), 14);
+        assert_eq!(home.size().unwrap(), 16);
+        assert!(ctx.verify_sizes().is_ok());
+    }
+
+    #[test]
+    fn resize_and_move() {
+        let ctx = build_tree("test_user");
+        let home = ctx.node_from_path(&vec![NodePathSegment::Root, NodePathSegment::Name("home".to_string())]).unwrap();
+        let documents = ctx.node_from_path(&vec![NodePathSegment::Name("documents".to_string())]).unwrap();
+        let photos = ctx.node_from_path(&vec![NodePathSegment::Name("photos".to_string())]).unwrap();
+
+        let cv = Rc::clone(&documents.children().unwrap().borrow()[0]);
+        Rc::clone(&cv).resize(10).unwrap();
+        assert_eq!(documents.size().unwrap(), 11);
+        assert_eq!(home.size().unwrap(), 21);
+
+        Rc::clone(&documents).move_to(Rc::clone(&photos)).unwrap();
+        assert_eq!(photos.size().unwrap(), 11);
+        assert_eq!(home.size().unwrap(), 21);
+        assert_eq!(cv.depth(), 5);
+        assert!(ctx.verify_sizes().is_ok());
+    }
+
+    #[test]
+    fn verify_sizes_reports_mismatch() {
+        let ctx = build_tree("test_user");
+        let music = ctx.node_from_path(&vec![NodePathSegment::Name("music".to_string())]).unwrap();
+        if let Node::Folder { size, .. } = &*music {
+            *size.borrow_mut() = 3;
+        }
+
+        let mismatches = ctx.verify_sizes().unwrap_err();
+        assert_eq!(mismatches, vec![SizeMismatch {
+            path: "/home/test_user/music".to_string(),
+            recorded: 3,
+            calculated: 10,
+        }]);
+    }
+
+    #[test]
+    fn deep_clone_is_independent() {
+        let ctx = build_tree("test_user");
+        let music = ctx.node_from_path(&vec![NodePathSegment::Name("music".to_string())]).unwrap();
+        let photos = ctx.node_from_path(&vec![NodePathSegment::Name("photos".to_string())]).unwrap();
+
+        let copy = music.deep_clone();
+        assert!(!music.is_ancestor_of(&copy));
+        Rc::clone(&photos).add(Rc::clone(&copy)).unwrap();
+
+        assert_eq!(photos.size().unwrap(), 10);
+        assert_eq!(copy.children().unwrap().borrow()[0].to_string(), "/home/test_user/photos/music/1.mp3");
+        assert!(photos.is_ancestor_of(&copy.children().unwrap().borrow()[0]));
+
+        Rc::clone(&copy).remove("1.mp3").unwrap();
+        assert_eq!(music.size().unwrap(), 10);
+        assert!(ctx.verify_sizes().is_ok());
+    }
+
+    #[test]
+    fn reject_duplicate_names() {
+        let ctx = build_tree("test_user");
+        let music = ctx.node_from_path(&vec![NodePathSegment::Name("music".to_string())]).unwrap();
+
+        let result = Rc::clone(&music).add(Rc::new(Node::new_file("1.mp3", 1)));
+        assert_eq!(result, Err(NodeError::AlreadyExists("1.mp3".to_string())));
+        assert!(Rc::clone(&music).add(Rc::new(Node::new_file("1.MP3", 1))).is_ok());
+        assert_eq!(music.size().unwrap(), 11);
+    }
+
+    #[test]
+    fn failed_move_changes_nothing() {
+        let ctx = build_tree("test_user");
+        let user = ctx.current_dir();
+        let music = user.child("music").unwrap();
+        let song = music.child("1.mp3").unwrap();
+
+        let result = ctx.move_node(Rc::clone(&song), Rc::clone(&music), "2.mp3");
+        assert_eq!(result, Err(NodeError::AlreadyExists("2.mp3".to_string())));
+        assert_eq!(song.to_string(), "/home/test_user/music/1.mp3");
+        assert!(Rc::ptr_eq(&music.child("1.mp3").unwrap(), &song));
+        assert!(!ctx.undo());
+
+        // renaming a node to its own name in a different case is not a clash.
+        ctx.set_case_insensitive(true);
+        assert!(ctx.move_node(Rc::clone(&music), Rc::clone(&user), "Music").is_ok());
+        assert_eq!(music.name().unwrap(), "Music");
+    }
+
+    #[test]
+    fn case_insensitive_names() {
+        let ctx = build_tree("test_user");
+        ctx.set_case_insensitive(true);
+        let music = ctx.node_from_path(&vec![NodePathSegment::Name("MUSIC".to_string())]).unwrap();
+
+        let result = Rc::clone(&music).add(Rc::new(Node::new_file("1.MP3", 1)));
+        assert_eq!(result, Err(NodeError::AlreadyExists("1.MP3".to_string())));
+        assert_eq!(music.child("1.Mp3").unwrap().name().unwrap(), "1.mp3");
+
+        // copies of the tree keep the setting.
+        let copy = ctx.root().deep_clone();
+        assert!(copy.is_case_insensitive());
+    }
+}